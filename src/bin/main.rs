@@ -1,5 +1,8 @@
 use clap::{Parser, Subcommand, ValueEnum};
-use sensitive_rs::{Filter, MatchAlgorithm};
+use sensitive_rs::{
+    Builtin, DictEntry, DictFormat, DictionaryReport, Filter, Markup, MatchAlgorithm, Precedence, canonicalize,
+    diff_entries, format_entries, merge_entries, read_entries, read_word_lists,
+};
 use serde::Serialize;
 use std::fs;
 use std::io::{self, IsTerminal, Read};
@@ -16,7 +19,7 @@ struct Cli {
     #[arg(long, global = true)]
     dict: Option<String>,
 
-    /// Use extended dictionary (dict/dict-all.txt)
+    /// Use the extended built-in dictionary
     #[arg(long, global = true)]
     dict_all: bool,
 
@@ -85,6 +88,102 @@ enum Commands {
         #[arg(short, long)]
         file: Vec<String>,
     },
+
+    /// Dictionary maintenance
+    Dict {
+        #[command(subcommand)]
+        command: DictCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum DictCommands {
+    /// Report unreachable, redundant, duplicate and overly short entries (exit 1 if any)
+    Lint {
+        /// Dictionary file to check (defaults to --dict, or the built-in dictionary)
+        file: Option<String>,
+    },
+
+    /// Show entries added, removed or changed from OLD to NEW (exit 1 if they differ)
+    Diff {
+        /// The old dictionary
+        old: String,
+
+        /// The new dictionary
+        new: String,
+    },
+
+    /// Merge dictionaries into one sorted, deduplicated dictionary
+    Merge {
+        /// Dictionaries, in precedence order
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Which entry wins when several dictionaries have the same word
+        #[arg(long, value_enum, default_value = "first")]
+        precedence: PrecedenceArg,
+
+        #[command(flatten)]
+        output: DictOutput,
+    },
+
+    /// Rewrite a dictionary in canonical form: sorted, deduplicated, normalized fields
+    Fmt {
+        /// Dictionary file
+        file: String,
+
+        #[command(flatten)]
+        output: DictOutput,
+    },
+}
+
+#[derive(clap::Args)]
+struct DictOutput {
+    /// Write to this file instead of stdout (format chosen by its extension)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Output format (defaults to the --output extension, or the line format)
+    #[arg(long, value_enum)]
+    format: Option<FormatArg>,
+}
+
+#[derive(Clone, ValueEnum)]
+enum PrecedenceArg {
+    First,
+    Last,
+    Overlay,
+}
+
+impl From<PrecedenceArg> for Precedence {
+    fn from(arg: PrecedenceArg) -> Self {
+        match arg {
+            PrecedenceArg::First => Precedence::First,
+            PrecedenceArg::Last => Precedence::Last,
+            PrecedenceArg::Overlay => Precedence::Overlay,
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum FormatArg {
+    Lines,
+    Csv,
+    Tsv,
+    Json,
+    Toml,
+}
+
+impl From<FormatArg> for DictFormat {
+    fn from(arg: FormatArg) -> Self {
+        match arg {
+            FormatArg::Lines => DictFormat::Lines,
+            FormatArg::Csv => DictFormat::Csv,
+            FormatArg::Tsv => DictFormat::Tsv,
+            FormatArg::Json => DictFormat::Json,
+            FormatArg::Toml => DictFormat::Toml,
+        }
+    }
 }
 
 #[derive(Clone, ValueEnum)]
@@ -127,7 +226,17 @@ struct ProcessResult {
     output: String,
 }
 
-fn build_filter(cli: &Cli) -> Filter {
+#[derive(Serialize)]
+struct LintIssue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    line: usize,
+    word: String,
+    issue: String,
+}
+
+/// A filter with the command-line settings and no dictionary.
+fn configured_filter(cli: &Cli) -> Filter {
     let mut filter =
         if let Some(algo) = &cli.algorithm { Filter::with_algorithm(algo.clone().into()) } else { Filter::new() };
 
@@ -138,19 +247,21 @@ fn build_filter(cli: &Cli) -> Filter {
         process::exit(1);
     }
 
+    filter
+}
+
+fn build_filter(cli: &Cli) -> Filter {
+    let mut filter = configured_filter(cli);
+
     if cli.dict_all {
-        if let Err(e) = filter.load_word_dict("dict/dict-all.txt") {
-            eprintln!("Error: failed to load extended dictionary: {e}");
-            process::exit(1);
-        }
+        filter.add_builtin(Builtin::All);
     } else if let Some(dict_path) = &cli.dict {
         if let Err(e) = filter.load_word_dict(dict_path) {
             eprintln!("Error: failed to load dictionary from '{}': {e}", dict_path);
             process::exit(1);
         }
-    } else if let Err(e) = filter.load_word_dict("dict/dict.txt") {
-        eprintln!("Error: failed to load default dictionary: {e}");
-        process::exit(1);
+    } else {
+        filter.add_builtin(Builtin::Default);
     }
 
     filter
@@ -222,8 +333,11 @@ fn cmd_check(cli: &Cli, filter: &Filter, texts: Vec<(String, Option<&str>)>) {
         } else {
             let label = source.map(|s| format!("[{s}] ")).unwrap_or_default();
             println!("{}Found {} sensitive word(s):", label, colored(&words.len().to_string(), "31", color),);
-            for word in &words {
-                println!("  {}", colored(word, "33", color));
+            // Print the text itself with every match highlighted in place. Control
+            // characters are replaced first so the input cannot drive the terminal.
+            let markup = if color { Markup::ansi() } else { Markup::Custom { open: "[".into(), close: "]".into() } };
+            for line in filter.annotate(&printable(text), &markup).lines() {
+                println!("  {line}");
             }
         }
     }
@@ -233,6 +347,12 @@ fn cmd_check(cli: &Cli, filter: &Filter, texts: Vec<(String, Option<&str>)>) {
     }
 }
 
+/// `text` with control characters other than newlines and tabs replaced by U+FFFD. Both
+/// are noise to the default filter, so matches are unaffected.
+fn printable(text: &str) -> String {
+    text.chars().map(|c| if c.is_control() && c != '\n' && c != '\t' { '\u{fffd}' } else { c }).collect()
+}
+
 fn cmd_validate(cli: &Cli, filter: &Filter, texts: Vec<(String, Option<&str>)>) {
     let mut all_clean = true;
     let mut all_results: Vec<serde_json::Value> = Vec::new();
@@ -310,8 +430,138 @@ fn cmd_filter(cli: &Cli, filter: &Filter, texts: Vec<(String, Option<&str>)>) {
     }
 }
 
+fn cmd_dict_lint(cli: &Cli, file: Option<&str>) {
+    // Each word list is linted on its own, so line numbers are per file.
+    let lists: Vec<(Option<String>, Vec<String>)> = match file.or(cli.dict.as_deref()) {
+        Some(path) => read_word_lists(path)
+            .unwrap_or_else(|e| {
+                eprintln!("Error: failed to read dictionary '{path}': {e}");
+                process::exit(1);
+            })
+            .into_iter()
+            .map(|(file, words)| (Some(file.display().to_string()), words))
+            .collect(),
+        None => {
+            let builtin = if cli.dict_all { Builtin::All } else { Builtin::Default };
+            vec![(None, builtin.text().lines().map(String::from).collect())]
+        }
+    };
+    let filter = configured_filter(cli);
+    let reports: Vec<(Option<&str>, DictionaryReport)> = lists
+        .iter()
+        .map(|(file, words)| (file.as_deref(), filter.lint(&words.iter().map(String::as_str).collect::<Vec<_>>())))
+        .collect();
+    let entries: usize = reports.iter().map(|(_, report)| report.entries).sum();
+    let clean = reports.iter().all(|(_, report)| report.is_clean());
+
+    if cli.json {
+        let issues: Vec<LintIssue> = reports
+            .iter()
+            .flat_map(|(file, report)| {
+                report.issues.iter().map(|issue| LintIssue {
+                    file: file.map(String::from),
+                    line: issue.index + 1,
+                    word: issue.word.clone(),
+                    issue: issue.kind.to_string(),
+                })
+            })
+            .collect();
+        let result = serde_json::json!({ "entries": entries, "issues": issues });
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else {
+        let color = use_color(cli);
+        let mut count = 0;
+        for (file, report) in &reports {
+            for issue in &report.issues {
+                match file {
+                    Some(file) if reports.len() > 1 => println!("{file}: {issue}"),
+                    _ => println!("{issue}"),
+                }
+            }
+            count += report.issues.len();
+        }
+        let summary = format!("{entries} entries, {count} issue(s)");
+        println!("{}", colored(&summary, if clean { "32" } else { "31" }, color));
+    }
+
+    if !clean {
+        process::exit(1);
+    }
+}
+
+fn load_entries(path: &str) -> Vec<DictEntry> {
+    read_entries(path).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load dictionary from '{path}': {e}");
+        process::exit(1);
+    })
+}
+
+fn write_dict(output: &DictOutput, entries: &[DictEntry]) {
+    let format = match (&output.format, &output.output) {
+        (Some(format), _) => format.clone().into(),
+        (None, Some(path)) => DictFormat::from_path(path),
+        (None, None) => DictFormat::Lines,
+    };
+    let text = format_entries(entries, format);
+    match &output.output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, text) {
+                eprintln!("Error: failed to write '{path}': {e}");
+                process::exit(1);
+            }
+        }
+        None => print!("{text}"),
+    }
+}
+
+fn cmd_dict_diff(cli: &Cli, old: &str, new: &str) {
+    let diff = diff_entries(&load_entries(old), &load_entries(new));
+
+    if cli.json {
+        let words = |entries: &[DictEntry]| entries.iter().map(|e| e.word.clone()).collect::<Vec<_>>();
+        let changed: Vec<serde_json::Value> = diff
+            .changed
+            .iter()
+            .map(|(old, new)| serde_json::json!({ "word": old.word, "old": old, "new": new }))
+            .collect();
+        let result = serde_json::json!({
+            "added": words(&diff.added),
+            "removed": words(&diff.removed),
+            "changed": changed,
+        });
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else {
+        let color = use_color(cli);
+        for line in diff.to_string().lines() {
+            let code = match line.as_bytes()[0] {
+                b'+' => "32",
+                b'-' => "31",
+                _ => "33",
+            };
+            println!("{}", colored(line, code, color));
+        }
+    }
+
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
+    if let Commands::Dict { command } = &cli.command {
+        match command {
+            DictCommands::Lint { file } => cmd_dict_lint(&cli, file.as_deref()),
+            DictCommands::Diff { old, new } => cmd_dict_diff(&cli, old, new),
+            DictCommands::Merge { files, precedence, output } => {
+                let dictionaries: Vec<Vec<DictEntry>> = files.iter().map(|path| load_entries(path)).collect();
+                let merged = merge_entries(dictionaries.iter().map(Vec::as_slice), precedence.clone().into());
+                write_dict(output, &merged);
+            }
+            DictCommands::Fmt { file, output } => write_dict(output, &canonicalize(&load_entries(file))),
+        }
+        return;
+    }
     let filter = build_filter(&cli);
 
     match &cli.command {
@@ -331,5 +581,6 @@ fn main() {
             let texts = resolve_texts(text, file);
             cmd_filter(&cli, &filter, texts);
         }
+        Commands::Dict { .. } => unreachable!("handled before loading the dictionary"),
     }
 }
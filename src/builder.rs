@@ -0,0 +1,313 @@
+//! Declarative [`Filter`] configuration.
+//!
+//! [`FilterBuilder`] collects every setting of a [`Filter`] — algorithm, match resolution,
+//! noise pattern, cache size, parallel threshold, whitespace folding, variant channels and the
+//! initial dictionary — validates them in [`FilterBuilder::build`] and compiles the engine
+//! once, over the whole dictionary.
+
+#[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+use crate::builtin::Builtin;
+use crate::engine::{MatchAlgorithm, MatchKind};
+use crate::filter::{self, Filter};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use regex::Regex;
+
+#[cfg(feature = "std")]
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Builder for a [`Filter`], created by [`Filter::builder`].
+///
+/// Every setting defaults to what [`Filter::new`] uses.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, MatchAlgorithm, MatchKind};
+///
+/// let filter = Filter::builder()
+///     .algorithm(MatchAlgorithm::AhoCorasick)
+///     .match_kind(MatchKind::LeftmostFirst)
+///     .noise_pattern(r"[^\w一-鿿]")
+///     .cache_capacity(10_000)
+///     .words(&["赌博", "色情"])
+///     .build()?;
+///
+/// assert_eq!(filter.current_algorithm(), MatchAlgorithm::AhoCorasick);
+/// assert_eq!(filter.replace("赌@博 色情", '*'), "****");
+/// # Ok::<(), sensitive_rs::BuildError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilterBuilder {
+    pub(crate) algorithm: Option<MatchAlgorithm>,
+    pub(crate) match_kind: MatchKind,
+    pub(crate) overlapping: bool,
+    pub(crate) noise: String,
+    pub(crate) fold_whitespace: bool,
+    #[cfg(feature = "std")]
+    pub(crate) cache_capacity: usize,
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel_threshold: usize,
+    #[cfg(feature = "std")]
+    pub(crate) pinyin_variants: bool,
+    #[cfg(feature = "std")]
+    pub(crate) shape_variants: bool,
+    words: Vec<String>,
+    #[cfg(feature = "std")]
+    dict_files: Vec<PathBuf>,
+}
+
+impl Default for FilterBuilder {
+    fn default() -> Self {
+        Self {
+            algorithm: None,
+            match_kind: MatchKind::default(),
+            overlapping: false,
+            noise: filter::DEFAULT_NOISE.to_string(),
+            fold_whitespace: true,
+            #[cfg(feature = "std")]
+            cache_capacity: filter::DEFAULT_CACHE_CAPACITY,
+            #[cfg(feature = "parallel")]
+            parallel_threshold: filter::DEFAULT_PARALLEL_THRESHOLD,
+            #[cfg(feature = "std")]
+            pinyin_variants: true,
+            #[cfg(feature = "std")]
+            shape_variants: true,
+            words: Vec::new(),
+            #[cfg(feature = "std")]
+            dict_files: Vec::new(),
+        }
+    }
+}
+
+impl FilterBuilder {
+    /// A builder with the default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force a matching algorithm instead of selecting one by dictionary size.
+    #[must_use]
+    pub fn algorithm(mut self, algorithm: MatchAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// How overlapping dictionary words are resolved (see [`Filter::set_match_kind`]).
+    #[must_use]
+    pub fn match_kind(mut self, match_kind: MatchKind) -> Self {
+        self.match_kind = match_kind;
+        self
+    }
+
+    /// Report nested and overlapping words (see [`Filter::set_overlapping`]).
+    #[must_use]
+    pub fn overlapping(mut self, overlapping: bool) -> Self {
+        self.overlapping = overlapping;
+        self
+    }
+
+    /// Regex of the noise characters stripped before matching (see
+    /// [`Filter::update_noise_pattern`]). Checked by [`FilterBuilder::build`].
+    #[must_use]
+    pub fn noise_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.noise = pattern.into();
+        self
+    }
+
+    /// Whether a word containing whitespace also matches with the whitespace removed, so
+    /// `"赌 博"` matches `赌博` (on by default).
+    #[must_use]
+    pub fn fold_whitespace(mut self, fold: bool) -> Self {
+        self.fold_whitespace = fold;
+        self
+    }
+
+    /// Number of [`Filter::find_all`] results kept in the LRU cache (1000 by default).
+    /// Must be at least 1.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Cleaned text longer than this many bytes is scanned in parallel by
+    /// [`Filter::find_all`] (1000 by default). `usize::MAX` never scans in parallel.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn parallel_threshold(mut self, bytes: usize) -> Self {
+        self.parallel_threshold = bytes;
+        self
+    }
+
+    /// Whether pinyin variants (`dubo` → `赌博`) are detected (on by default).
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn pinyin_variants(mut self, enabled: bool) -> Self {
+        self.pinyin_variants = enabled;
+        self
+    }
+
+    /// Whether shape variants (`睹博` → `赌博`) are detected (on by default).
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn shape_variants(mut self, enabled: bool) -> Self {
+        self.shape_variants = enabled;
+        self
+    }
+
+    /// Add words to the initial dictionary.
+    #[must_use]
+    pub fn words(mut self, words: &[&str]) -> Self {
+        self.words.extend(words.iter().map(|word| word.to_string()));
+        self
+    }
+
+    /// Add the words of a dictionary compiled into the crate.
+    #[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+    #[must_use]
+    pub fn builtin(mut self, builtin: Builtin) -> Self {
+        self.words.extend(builtin.words().map(str::to_string));
+        self
+    }
+
+    /// Add the words of a dictionary file, one per line, read by [`FilterBuilder::build`].
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn dict_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.dict_files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Validate the settings, read the dictionary files and build the filter, compiling
+    /// the engine once over the whole dictionary.
+    ///
+    /// # Errors
+    ///
+    /// [`BuildError`] if the noise pattern is not a valid regex, the cache capacity is 0, or
+    /// a dictionary file cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{BuildError, Filter};
+    ///
+    /// let error = Filter::builder().noise_pattern("[").build().unwrap_err();
+    /// assert!(matches!(error, BuildError::NoisePattern(_)));
+    /// ```
+    pub fn build(mut self) -> Result<Filter, BuildError> {
+        let noise = Regex::new(&self.noise).map_err(BuildError::NoisePattern)?;
+        #[cfg(feature = "std")]
+        if self.cache_capacity == 0 {
+            return Err(BuildError::ZeroCacheCapacity);
+        }
+
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut words = core::mem::take(&mut self.words);
+        #[cfg(feature = "std")]
+        for path in &self.dict_files {
+            let text =
+                fs::read_to_string(path).map_err(|source| BuildError::Dictionary { path: path.clone(), source })?;
+            words.extend(text.lines().map(str::to_string));
+        }
+
+        let mut filter = Filter::configured(&self, noise);
+        filter.add_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+        Ok(filter)
+    }
+}
+
+/// Invalid [`FilterBuilder`] settings, reported by [`FilterBuilder::build`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The noise pattern is not a valid regex.
+    NoisePattern(regex::Error),
+    /// The result cache capacity is 0.
+    ZeroCacheCapacity,
+    /// A dictionary file could not be read.
+    #[cfg(feature = "std")]
+    Dictionary {
+        /// The file.
+        path: PathBuf,
+        /// Why it could not be read.
+        source: io::Error,
+    },
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoisePattern(e) => write!(f, "invalid noise pattern: {e}"),
+            Self::ZeroCacheCapacity => f.write_str("cache capacity must be at least 1"),
+            #[cfg(feature = "std")]
+            Self::Dictionary { path, source } => write!(f, "cannot read dictionary {}: {source}", path.display()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NoisePattern(e) => Some(e),
+            Self::ZeroCacheCapacity => None,
+            Self::Dictionary { source, .. } => Some(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_new() {
+        let built = FilterBuilder::new().words(&["赌博", "赌 博"]).build().unwrap();
+        let mut plain = Filter::new();
+        plain.add_words(&["赌博", "赌 博"]);
+        let text = "赌@博 dubo 睹博";
+        assert_eq!(built.find_all(text), plain.find_all(text));
+        assert_eq!(built.current_algorithm(), plain.current_algorithm());
+        assert_eq!(built.remove_noise("a@b"), plain.remove_noise("a@b"));
+    }
+
+    #[test]
+    fn test_settings_are_applied() {
+        let builder = FilterBuilder::new()
+            .algorithm(MatchAlgorithm::Regex)
+            .match_kind(MatchKind::LeftmostFirst)
+            .overlapping(true)
+            .fold_whitespace(false)
+            .pinyin_variants(false)
+            .shape_variants(false);
+        #[cfg(feature = "parallel")]
+        let builder = builder.parallel_threshold(0);
+        let filter = builder.words(&["赌", "赌博", "色 情"]).build().unwrap();
+        assert_eq!(filter.current_algorithm(), MatchAlgorithm::Regex);
+        assert_eq!(filter.match_kind(), MatchKind::LeftmostFirst);
+        assert!(filter.is_overlapping());
+        // No folded form, no variants; with `parallel`, the parallel path gives the same result.
+        assert_eq!(filter.find_all("赌博 色情 dubo 睹博"), ["赌", "赌博"]);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        assert!(matches!(FilterBuilder::new().cache_capacity(0).build(), Err(BuildError::ZeroCacheCapacity)));
+        let error = FilterBuilder::new().dict_file("no/such/dict.txt").build().unwrap_err();
+        assert!(matches!(&error, BuildError::Dictionary { path, .. } if path.ends_with("dict.txt")));
+        assert!(error.to_string().starts_with("cannot read dictionary no/such/dict.txt"));
+    }
+
+    #[test]
+    fn test_dict_file_is_loaded() {
+        let filter = FilterBuilder::new().dict_file("dict/dict.txt").words(&["自定义词"]).build().unwrap();
+        assert!(filter.find_in("自定义词").0);
+        assert!(filter.engine().get_patterns().len() > 1);
+    }
+}
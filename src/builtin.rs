@@ -0,0 +1,85 @@
+//! Dictionaries compiled into the crate.
+//!
+//! The bundled word lists are embedded with `include_str!` behind the `builtin-dict`
+//! (`dict/dict.txt`) and `builtin-dict-all` (`dict/dict-all.txt`) features, so they work
+//! wherever the crate is used: from another crate, after `cargo install`, and on `wasm`
+//! without filesystem access.
+
+/// A dictionary embedded in the crate.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Builtin, Filter};
+///
+/// let filter = Filter::with_builtin(Builtin::Default);
+/// assert!(filter.find_in("sex").0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Builtin {
+    /// The standard dictionary, `dict/dict.txt` (feature `builtin-dict`).
+    #[cfg(feature = "builtin-dict")]
+    Default,
+    /// The extended dictionary, `dict/dict-all.txt` (feature `builtin-dict-all`).
+    #[cfg(feature = "builtin-dict-all")]
+    All,
+}
+
+impl Builtin {
+    /// Every dictionary compiled into this build.
+    pub const ENABLED: &'static [Builtin] = &[
+        #[cfg(feature = "builtin-dict")]
+        Builtin::Default,
+        #[cfg(feature = "builtin-dict-all")]
+        Builtin::All,
+    ];
+
+    /// The dictionary file's text, one word per line.
+    pub fn text(self) -> &'static str {
+        match self {
+            #[cfg(feature = "builtin-dict")]
+            Self::Default => include_str!("../dict/dict.txt"),
+            #[cfg(feature = "builtin-dict-all")]
+            Self::All => include_str!("../dict/dict-all.txt"),
+        }
+    }
+
+    /// The dictionary's words, skipping blank lines.
+    pub fn words(self) -> impl Iterator<Item = &'static str> {
+        self.text().lines().map(str::trim_end).filter(|line| !line.is_empty())
+    }
+
+    /// The dictionary's name: `"default"` or `"all"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "builtin-dict")]
+            Self::Default => "default",
+            #[cfg(feature = "builtin-dict-all")]
+            Self::All => "all",
+        }
+    }
+
+    /// The enabled dictionary called `name` (see [`Builtin::name`]).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ENABLED.iter().copied().find(|builtin| builtin.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Filter;
+
+    #[test]
+    fn test_builtin_words_match_files() {
+        for &builtin in Builtin::ENABLED {
+            assert!(builtin.words().all(|word| !word.is_empty()));
+            assert_eq!(Builtin::from_name(builtin.name()), Some(builtin));
+            let filter = Filter::with_builtin(builtin);
+            let first = builtin.words().next().unwrap();
+            assert!(filter.find_in(first).0);
+        }
+        assert_eq!(Builtin::from_name("missing"), None);
+    }
+}
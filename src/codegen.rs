@@ -0,0 +1,270 @@
+//! Build-script helpers that compile a dictionary into Rust source.
+//!
+//! [`write_static_dict`] reads a word list, expands it exactly as [`Filter::add_words`]
+//! would, builds the Aho-Corasick automaton and the pinyin index, and writes them as a
+//! `static` [`StaticDict`](crate::StaticDict). The crate that includes the file pays none of that at runtime:
+//! [`Filter::from_static`] scans the generated tables directly, on `no_std` too.
+//!
+//! ```no_run
+//! // build.rs, with `sensitive-rs = { version = "2.0", features = ["codegen"] }`
+//! // under [build-dependencies]
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("dict.rs");
+//! sensitive_rs::codegen::write_static_dict("dict/words.txt", out, "WORDS").unwrap();
+//! ```
+//!
+//! ```ignore
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/dict.rs"));
+//!
+//! let filter = sensitive_rs::Filter::from_static(&WORDS);
+//! ```
+
+use crate::dict::{DictFormat, parse_entries};
+#[cfg(test)]
+use crate::engine::StaticDict;
+use crate::filter::Filter;
+use alloc::collections::{BTreeMap, VecDeque};
+use core::fmt::Write as _;
+use std::{fs, io, path::Path};
+
+/// Compile the dictionary file at `dict_path` into a `pub static <name>: sensitive_rs::StaticDict`
+/// written to `out_path`.
+///
+/// The file is read as the line format of [`DictFormat::Lines`]: one word per line, with
+/// blank lines and `#` comments skipped. Only the words are compiled; metadata fields are
+/// not kept.
+///
+/// Prints `cargo:rerun-if-changed` for the dictionary, so a build script calling this is
+/// re-run when the file changes.
+///
+/// # Errors
+///
+/// If the dictionary cannot be read or parsed ([`io::ErrorKind::InvalidData`]), or the output
+/// cannot be written.
+pub fn write_static_dict(dict_path: impl AsRef<Path>, out_path: impl AsRef<Path>, name: &str) -> io::Result<()> {
+    let dict_path = dict_path.as_ref();
+    println!("cargo:rerun-if-changed={}", dict_path.display());
+    let text = fs::read_to_string(dict_path)?;
+    let entries = parse_entries(&text, DictFormat::Lines)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {e}", dict_path.display())))?;
+    let words: Vec<&str> = entries.iter().map(|entry| entry.word.as_str()).collect();
+    fs::write(out_path, static_dict_source(&words, name))
+}
+
+/// Rust source declaring `pub static <name>: sensitive_rs::StaticDict` for `words`.
+pub fn static_dict_source(words: &[&str], name: &str) -> String {
+    Tables::compile(words).to_source(name)
+}
+
+/// The tables of a [`StaticDict`](crate::StaticDict), before they are written out.
+#[derive(Debug, Default)]
+struct Tables {
+    text: String,
+    ends: Vec<u32>,
+    nodes: Vec<u32>,
+    edge_bytes: Vec<u8>,
+    edge_targets: Vec<u32>,
+    outputs: Vec<u32>,
+    pinyin: Vec<(String, u32)>,
+    char_pinyin: Vec<(char, String)>,
+}
+
+impl Tables {
+    fn compile(words: &[&str]) -> Self {
+        // Let a filter expand the words, so the forms are exactly those `add_words` adds.
+        let words: Vec<&str> = words.iter().copied().filter(|word| !word.is_empty()).collect();
+        let mut filter = Filter::new();
+        filter.add_words(&words);
+        let patterns = filter.engine().get_patterns();
+
+        let mut tables = Tables::default();
+        let ids: BTreeMap<&str, u32> = patterns.iter().enumerate().map(|(id, p)| (p.as_str(), id as u32)).collect();
+        for pattern in patterns {
+            tables.text.push_str(pattern);
+            tables.ends.push(u32::try_from(tables.text.len()).expect("dictionary under 4 GiB"));
+        }
+        tables.build_automaton(patterns);
+        let detector = filter.variant_detector();
+        tables.pinyin = detector.pinyin_entries().map(|(key, word)| (key.to_string(), ids[word])).collect();
+        tables.pinyin.sort_unstable();
+        tables.char_pinyin = detector.char_pinyin().map(|(c, pinyin)| (c, pinyin.to_string())).collect();
+        tables.char_pinyin.sort_unstable();
+        tables
+    }
+
+    /// Trie over the pattern bytes, then failure links and output sets breadth-first.
+    fn build_automaton(&mut self, patterns: &[String]) {
+        let mut children: Vec<BTreeMap<u8, u32>> = vec![BTreeMap::new()];
+        let mut outputs: Vec<Vec<u32>> = vec![Vec::new()];
+        for (id, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &byte in pattern.as_bytes() {
+                let next = children.len() as u32;
+                state = *children[state].entry(byte).or_insert(next) as usize;
+                if state == next as usize {
+                    children.push(BTreeMap::new());
+                    outputs.push(Vec::new());
+                }
+            }
+            outputs[state].push(id as u32);
+        }
+
+        let mut fail = vec![0u32; children.len()];
+        let mut queue: VecDeque<usize> = children[0].values().map(|&child| child as usize).collect();
+        while let Some(state) = queue.pop_front() {
+            for (&byte, &child) in &children[state] {
+                let child = child as usize;
+                let mut f = fail[state] as usize;
+                fail[child] = loop {
+                    if let Some(&target) = children[f].get(&byte) {
+                        break target;
+                    }
+                    if f == 0 {
+                        break 0;
+                    }
+                    f = fail[f] as usize;
+                };
+                // Shallower nodes are complete by now: inherit the failure node's outputs.
+                let inherited = outputs[fail[child] as usize].clone();
+                outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        for (state, edges) in children.iter().enumerate() {
+            self.nodes.extend([
+                self.edge_bytes.len() as u32,
+                edges.len() as u32,
+                fail[state],
+                self.outputs.len() as u32,
+                outputs[state].len() as u32,
+            ]);
+            self.edge_bytes.extend(edges.keys());
+            self.edge_targets.extend(edges.values());
+            self.outputs.extend(&outputs[state]);
+        }
+    }
+
+    fn to_source(&self, name: &str) -> String {
+        fn numbers(out: &mut String, values: &[u32]) {
+            out.push_str("&[");
+            for v in values {
+                write!(out, "{v},").unwrap();
+            }
+            out.push(']');
+        }
+
+        let mut out = String::from("// Generated by sensitive_rs::codegen. Do not edit.\n");
+        writeln!(out, "pub static {name}: ::sensitive_rs::StaticDict = ::sensitive_rs::StaticDict::from_parts(")
+            .unwrap();
+        writeln!(out, "    {:?},", self.text).unwrap();
+        for values in [&self.ends, &self.nodes] {
+            out.push_str("    ");
+            numbers(&mut out, values);
+            out.push_str(",\n");
+        }
+        out.push_str("    b\"");
+        for byte in &self.edge_bytes {
+            write!(out, "\\x{byte:02x}").unwrap();
+        }
+        out.push_str("\",\n");
+        for values in [&self.edge_targets, &self.outputs] {
+            out.push_str("    ");
+            numbers(&mut out, values);
+            out.push_str(",\n");
+        }
+        out.push_str("    &[");
+        for (key, id) in &self.pinyin {
+            write!(out, "({key:?},{id}),").unwrap();
+        }
+        out.push_str("],\n    &[");
+        for (c, pinyin) in &self.char_pinyin {
+            write!(out, "({c:?},{pinyin:?}),").unwrap();
+        }
+        out.push_str("],\n);\n");
+        out
+    }
+
+    /// The tables as a live dictionary, as the generated source would define it.
+    #[cfg(test)]
+    fn leak(self) -> &'static StaticDict {
+        fn leak<T>(v: Vec<T>) -> &'static [T] {
+            Vec::leak(v)
+        }
+        let pinyin = self.pinyin.into_iter().map(|(key, id)| (&*String::leak(key), id)).collect();
+        let char_pinyin = self.char_pinyin.into_iter().map(|(c, pinyin)| (c, &*String::leak(pinyin))).collect();
+        Box::leak(Box::new(StaticDict::from_parts(
+            String::leak(self.text),
+            leak(self.ends),
+            leak(self.nodes),
+            leak(self.edge_bytes),
+            leak(self.edge_targets),
+            leak(self.outputs),
+            leak(pinyin),
+            leak(char_pinyin),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MatchKind, MultiPatternEngine};
+
+    const WORDS: &[&str] = &["赌博", "赌博机", "博机", "色 情", "he", "she", "his", "hers", ""];
+
+    #[test]
+    fn test_static_filter_matches_runtime_filter() {
+        let dict = Tables::compile(WORDS).leak();
+        let mut runtime = Filter::new();
+        runtime.add_words(&WORDS[..WORDS.len() - 1]);
+        let mut prebuilt = Filter::from_static(dict);
+        assert_eq!(dict.patterns().collect::<Vec<_>>(), runtime.engine().get_patterns());
+
+        for text in ["赌博机和赌博", "色情 色 情", "ushers his", "dubo 睹博", "无"] {
+            assert_eq!(prebuilt.find_all(text), runtime.find_all(text), "{text}");
+            assert_eq!(prebuilt.find_occurrences(text), runtime.find_occurrences(text), "{text}");
+            assert_eq!(prebuilt.replace(text, '*'), runtime.replace(text, '*'), "{text}");
+        }
+        // Settings and later words work on top of the prebuilt automaton.
+        for filter in [&mut prebuilt, &mut runtime] {
+            filter.set_match_kind(MatchKind::LeftmostFirst);
+            filter.add_word("机和");
+        }
+        assert_eq!(prebuilt.find_all("赌博机和赌博"), runtime.find_all("赌博机和赌博"));
+    }
+
+    #[test]
+    fn test_overlapping_scan_finds_every_occurrence() {
+        let dict = Tables::compile(&["he", "she", "his", "hers"]).leak();
+        let mut found: Vec<_> = dict.find_overlapping_iter("ushers").map(|(s, e, _)| (s, e)).collect();
+        found.sort_unstable();
+        assert_eq!(found, [(1, 4), (2, 4), (2, 6)]);
+        assert!(!dict.is_match("hi"));
+        let engine = MultiPatternEngine::from_static(dict);
+        assert_eq!(engine.find_all("ushers"), ["she"]);
+    }
+
+    #[test]
+    fn test_dictionary_file_comments_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("sensitive-rs-codegen-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("words.txt"), "# gambling\n赌博 # common\n\n色情|category=porn\n").unwrap();
+        write_static_dict(dir.join("words.txt"), dir.join("dict.rs"), "WORDS").unwrap();
+        let source = fs::read_to_string(dir.join("dict.rs")).unwrap();
+        assert!(source.contains("\"赌博色情\""), "{source}");
+
+        fs::write(dir.join("bad.txt"), "赌博|weight\n").unwrap();
+        let error = write_static_dict(dir.join("bad.txt"), dir.join("bad.rs"), "WORDS").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_source_declares_static() {
+        let source = static_dict_source(&["赌博"], "WORDS");
+        assert!(source.contains("pub static WORDS: ::sensitive_rs::StaticDict"));
+        assert!(source.contains("\"赌博\""));
+        assert!(source.contains("(\"dubo\",0)"));
+    }
+}
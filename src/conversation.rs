@@ -0,0 +1,276 @@
+//! Detection across consecutive messages of a conversation.
+//!
+//! A word split over several short messages ("赌", "博") passes any per-message check.
+//! [`Conversation`] keeps a bounded window of each sender's recent messages and matches over
+//! their concatenation, reporting which messages (and which spans of them) formed each hit.
+//! The number of senders tracked is bounded too: the least recently active one is dropped
+//! to make room for a new one.
+
+use crate::filter::{Filter, Match};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::Hash;
+use hashbrown::HashMap;
+
+/// Recent messages per sender, matched together so words split across messages are found.
+///
+/// Each sender's messages are joined without a separator and with the whitespace at their
+/// edges left out, so a word may be cut at any message boundary: "赌 " followed by " 博" is
+/// found. Noise between the pieces is skipped as usual. Only the last `window` messages of a
+/// sender are kept, for at most `max_senders` senders.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Conversation, Filter};
+///
+/// let mut filter = Filter::new();
+/// filter.add_word("赌博");
+///
+/// let mut conversation = Conversation::new(5, 10_000);
+/// assert!(conversation.push(&filter, "alice", 1, "赌").is_empty());
+/// assert!(conversation.push(&filter, "bob", 2, "博").is_empty());
+/// let hits = conversation.push(&filter, "alice", 3, "博来");
+///
+/// assert_eq!(hits[0].word, "赌博");
+/// let parts: Vec<_> = hits[0].parts.iter().map(|p| (p.message_id, p.start, p.end)).collect();
+/// assert_eq!(parts, [(1, 0, 3), (3, 0, 3)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Conversation<S, M> {
+    window: usize,
+    max_senders: usize,
+    senders: HashMap<S, Window<M>>,
+    recency: BTreeMap<u64, S>, // Senders by the tick of their latest message, oldest first
+    tick: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Window<M> {
+    messages: VecDeque<Message<M>>,
+    tick: u64, // When the sender's latest message arrived
+}
+
+#[derive(Debug, Clone)]
+struct Message<M> {
+    id: M,
+    text: String,
+}
+
+/// Where a message's text, trimmed, sits in the joined window.
+struct Piece {
+    from: usize, // Offset in the joined text
+    lead: usize, // Leading whitespace left out, in bytes
+    len: usize,  // Length of the trimmed text
+}
+
+/// A dictionary word found by [`Conversation::push`], possibly spread over several messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationHit<M> {
+    /// The matched sensitive word, in dictionary form.
+    pub word: String,
+    /// `true` if matched via a pinyin/shape variant rather than an exact hit.
+    pub is_variant: bool,
+    /// The messages that contributed to the hit, oldest first. A single part means the word
+    /// was found within one message.
+    pub parts: Vec<HitPart<M>>,
+}
+
+/// The piece of one message that is part of a [`ConversationHit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitPart<M> {
+    /// ID of the message, as passed to [`Conversation::push`].
+    pub message_id: M,
+    /// Byte offset where the piece starts in the message text.
+    pub start: usize,
+    /// Byte offset where the piece ends (exclusive) in the message text.
+    pub end: usize,
+}
+
+impl<S: Hash + Eq + Clone, M: Clone> Conversation<S, M> {
+    /// Keep the last `window` messages of each sender, for the `max_senders` most recently
+    /// active senders (both at least 1).
+    #[must_use]
+    pub fn new(window: usize, max_senders: usize) -> Self {
+        Self {
+            window: window.max(1),
+            max_senders: max_senders.max(1),
+            senders: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Record a message from `sender` and return the hits that involve it.
+    ///
+    /// Hits are resolved over the sender's window as [`Filter::find_occurrences`] resolves
+    /// them; hits lying wholly in earlier messages were reported when those arrived and are
+    /// not repeated. Filter on `parts.len() > 1` to keep only hits that per-message checks
+    /// would miss.
+    ///
+    /// A sender not yet tracked when `max_senders` are takes the place of the one whose latest
+    /// message is the oldest.
+    pub fn push(&mut self, filter: &Filter, sender: S, message_id: M, text: &str) -> Vec<ConversationHit<M>> {
+        self.tick += 1;
+        if let Some(window) = self.senders.get_mut(&sender) {
+            self.recency.remove(&window.tick);
+            window.tick = self.tick;
+        } else {
+            if self.senders.len() >= self.max_senders {
+                if let Some((_, idle)) = self.recency.pop_first() {
+                    self.senders.remove(&idle);
+                }
+            }
+            self.senders.insert(sender.clone(), Window { messages: VecDeque::new(), tick: self.tick });
+        }
+        self.recency.insert(self.tick, sender.clone());
+        let messages = &mut self.senders.get_mut(&sender).expect("inserted above").messages;
+        messages.push_back(Message { id: message_id, text: text.into() });
+        while messages.len() > self.window {
+            messages.pop_front();
+        }
+
+        let mut joined = String::new();
+        let mut pieces = Vec::with_capacity(messages.len());
+        for message in messages.iter() {
+            let trimmed = message.text.trim();
+            let lead = message.text.len() - message.text.trim_start().len();
+            pieces.push(Piece { from: joined.len(), lead, len: trimmed.len() });
+            joined.push_str(trimmed);
+        }
+        let newest = pieces.last().map_or(0, |piece| piece.from);
+
+        filter
+            .find_occurrences(&joined)
+            .into_iter()
+            .filter(|m| m.end > newest)
+            .map(|m| Self::hit(messages, &pieces, m))
+            .collect()
+    }
+
+    /// Drop the window of `sender`, e.g. when their session ends. Returns whether it existed.
+    pub fn forget(&mut self, sender: &S) -> bool {
+        match self.senders.remove(sender) {
+            Some(window) => self.recency.remove(&window.tick).is_some(),
+            None => false,
+        }
+    }
+
+    /// Drop every sender's window.
+    pub fn clear(&mut self) {
+        self.senders.clear();
+        self.recency.clear();
+    }
+
+    /// Number of senders with messages in the window.
+    #[must_use]
+    pub fn senders(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Split a match over the joined text into the messages it covers.
+    fn hit(messages: &VecDeque<Message<M>>, pieces: &[Piece], m: Match) -> ConversationHit<M> {
+        let parts = messages
+            .iter()
+            .zip(pieces)
+            .filter_map(|(message, piece)| {
+                let start = m.start.max(piece.from);
+                let end = m.end.min(piece.from + piece.len);
+                let offset = |at: usize| at - piece.from + piece.lead;
+                (start < end).then(|| HitPart {
+                    message_id: message.id.clone(),
+                    start: offset(start),
+                    end: offset(end),
+                })
+            })
+            .collect();
+        ConversationHit { word: m.word, is_variant: m.is_variant, parts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_with(words: &[&str]) -> Filter {
+        let mut filter = Filter::new();
+        filter.add_words(words);
+        filter
+    }
+
+    fn spans(hit: &ConversationHit<u32>) -> Vec<(u32, usize, usize)> {
+        hit.parts.iter().map(|p| (p.message_id, p.start, p.end)).collect()
+    }
+
+    #[test]
+    fn test_word_split_over_three_messages() {
+        let filter = filter_with(&["赌博来"]);
+        let mut conversation = Conversation::new(3, 10);
+        assert!(conversation.push(&filter, 'a', 1, "赌").is_empty());
+        assert!(conversation.push(&filter, 'a', 2, "@博").is_empty());
+        let hits = conversation.push(&filter, 'a', 3, "来啊");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(spans(&hits[0]), [(1, 0, 3), (2, 0, 4), (3, 0, 3)]);
+    }
+
+    #[test]
+    fn test_whitespace_at_message_edges_is_a_gap() {
+        let filter = filter_with(&["赌博"]);
+        let mut conversation = Conversation::new(3, 10);
+        assert!(conversation.push(&filter, 'a', 1, "赌 ").is_empty());
+        let hits = conversation.push(&filter, 'a', 2, "  博 ");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(spans(&hits[0]), [(1, 0, 3), (2, 2, 5)]);
+        // A message of whitespace only does not split a word either.
+        conversation.push(&filter, 'b', 3, "赌");
+        conversation.push(&filter, 'b', 4, " \n");
+        assert_eq!(spans(&conversation.push(&filter, 'b', 5, "\t博")[0]), [(3, 0, 3), (5, 1, 4)]);
+    }
+
+    #[test]
+    fn test_hits_reported_once() {
+        let filter = filter_with(&["赌博"]);
+        let mut conversation = Conversation::new(4, 10);
+        let hits = conversation.push(&filter, 'a', 1, "赌博");
+        assert_eq!(spans(&hits[0]), [(1, 0, 6)]);
+        assert!(conversation.push(&filter, 'a', 2, "好").is_empty());
+        let hits = conversation.push(&filter, 'a', 3, "赌博");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(spans(&hits[0]), [(3, 0, 6)]);
+    }
+
+    #[test]
+    fn test_window_and_senders_are_bounded() {
+        let filter = filter_with(&["赌博"]);
+        let mut conversation = Conversation::new(2, 10);
+        conversation.push(&filter, 'a', 1, "赌");
+        conversation.push(&filter, 'b', 2, "博");
+        conversation.push(&filter, 'a', 3, "@");
+        // "赌@博" would match, but "赌" has left the window of `a`.
+        assert!(conversation.push(&filter, 'a', 4, "博").is_empty());
+        assert_eq!(conversation.senders(), 2);
+        assert!(conversation.forget(&'b'));
+        assert!(!conversation.forget(&'b'));
+        assert_eq!(conversation.senders(), 1);
+    }
+
+    #[test]
+    fn test_least_recently_active_sender_is_dropped() {
+        let filter = filter_with(&["赌博"]);
+        let mut conversation = Conversation::new(4, 2);
+        conversation.push(&filter, 'a', 1, "赌");
+        conversation.push(&filter, 'b', 2, "赌");
+        conversation.push(&filter, 'a', 3, "@");
+        // `c` takes the place of `b`, whose latest message is older than that of `a`.
+        conversation.push(&filter, 'c', 4, "赌");
+        assert_eq!(conversation.senders(), 2);
+        assert!(conversation.push(&filter, 'b', 5, "博").is_empty());
+        // `b` has in turn displaced `a`, and `a` now displaces `c`.
+        assert!(conversation.push(&filter, 'a', 6, "博").is_empty());
+        assert!(conversation.push(&filter, 'b', 7, "赌").is_empty());
+        assert_eq!(spans(&conversation.push(&filter, 'b', 8, "博")[0]), [(7, 0, 3), (8, 0, 3)]);
+        assert_eq!(conversation.senders(), 2);
+        assert!(!conversation.forget(&'c'));
+    }
+}
@@ -0,0 +1,207 @@
+//! Finding and opening dictionary files: directories, file name patterns and compressed files.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Suffixes of compressed files, looked through for the name of the file inside.
+const COMPRESSED: [&str; 2] = ["gz", "zst"];
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The files `path` names, in name order: the word lists directly inside a directory
+/// (hidden ones skipped), the word lists matching a pattern, or else `path` itself.
+///
+/// Only `.txt` files, possibly compressed (`.txt.gz`, `.txt.zst`), are word lists; other
+/// files in a directory or matching a pattern are passed over. A pattern has `*` (any run
+/// of characters) or `?` (any one character) in its file name only, as in `dict/*.txt`;
+/// hidden files match only a pattern starting with `.`. A directory or pattern without
+/// any word list is a [`io::ErrorKind::NotFound`] error.
+pub(crate) fn dictionary_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let file = entry?.path();
+            let hidden = file.file_name().is_some_and(|name| name.as_encoded_bytes().starts_with(b"."));
+            if !hidden && is_word_list(&file) && file.is_file() {
+                files.push(file);
+            }
+        }
+    } else if is_pattern(path) {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {message}", path.display()));
+        let pattern = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| invalid("non-UTF-8 pattern"))?;
+        let parent = path.parent().unwrap_or(Path::new(""));
+        if parent.to_str().is_none_or(|parent| parent.contains(['*', '?'])) {
+            return Err(invalid("wildcards are only supported in the file name"));
+        }
+        let listed = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+        for entry in std::fs::read_dir(listed)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            let hidden = name.starts_with('.') && !pattern.starts_with('.');
+            if !hidden && wildcard_match(pattern, name) && is_word_list(Path::new(name)) && entry.path().is_file() {
+                files.push(parent.join(name));
+            }
+        }
+    } else {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if files.is_empty() {
+        let message = format!("{}: no `.txt` word lists", path.display());
+        return Err(io::Error::new(io::ErrorKind::NotFound, message));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `path` is a plain word list: a `.txt` file, possibly compressed.
+fn is_word_list(path: &Path) -> bool {
+    uncompressed_path(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("txt"))
+}
+
+/// Whether `path` is a pattern rather than an existing file.
+fn is_pattern(path: &Path) -> bool {
+    !path.exists() && path.to_str().is_some_and(|p| p.contains(['*', '?']))
+}
+
+/// Whether `name` matches `pattern`, in which `*` stands for any run of characters and `?`
+/// for any one character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Pattern position after the last `*`, and the name position that `*` matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            // Mismatch: let the last `*` take one more character, if there is one.
+            _ => match star {
+                Some((after, upto)) => {
+                    star = Some((after, upto + 1));
+                    (p, n) = (after, upto + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// `path` without a compression suffix (`words.csv.gz` → `words.csv`).
+pub(crate) fn uncompressed_path(path: &Path) -> PathBuf {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) if COMPRESSED.iter().any(|c| extension.eq_ignore_ascii_case(c)) => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The name a file's words are loaded under: its stem, after any compression suffix
+/// (`dict/gambling.txt.gz` → `gambling`).
+pub(crate) fn file_stem(path: &Path) -> String {
+    uncompressed_path(path).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Open `path` for reading, decompressing it if its content is gzip (feature `dict-gzip`)
+/// or zstd (feature `dict-zstd`) compressed.
+pub(crate) fn open(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "dict-gzip")]
+        return Ok(Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))));
+        #[cfg(not(feature = "dict-gzip"))]
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "gzip-compressed; enable the `dict-gzip` feature"));
+    }
+    if magic.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "dict-zstd")]
+        {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(reader)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            return Ok(Box::new(BufReader::new(decoder)));
+        }
+        #[cfg(not(feature = "dict-zstd"))]
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "zstd-compressed; enable the `dict-zstd` feature"));
+    }
+    Ok(Box::new(reader))
+}
+
+/// The lines of the word list `path`, as written. Errors name the file, and the line where
+/// reading failed.
+pub(crate) fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    let context = |line: Option<usize>, e: io::Error| {
+        let line = line.map(|line| format!(": line {line}")).unwrap_or_default();
+        io::Error::new(e.kind(), format!("{}{line}: {e}", path.display()))
+    };
+    let reader = open(path).map_err(|e| context(None, e))?;
+    reader.lines().enumerate().map(|(i, line)| line.map_err(|e| context(Some(i + 1), e))).collect()
+}
+
+/// The words of the word list `path`: its lines, trimmed, with blank ones skipped.
+pub(crate) fn read_words(path: &Path) -> io::Result<Vec<String>> {
+    let mut words = read_lines(path)?;
+    words.retain_mut(|line| {
+        let word = line.trim();
+        if word.len() != line.len() {
+            *line = String::from(word);
+        }
+        !line.is_empty()
+    });
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcards() {
+        for (pattern, name) in [("*", ""), ("*.txt", "a.txt"), ("a*b*c", "abbbc"), ("?博*", "赌博.txt"), ("**x", "x")]
+        {
+            assert!(wildcard_match(pattern, name), "{pattern} {name}");
+        }
+        for (pattern, name) in [("?", ""), ("*.txt", "a.txt.gz"), ("a*b*c", "abcb"), ("a?", "a"), ("[ab]", "a")] {
+            assert!(!wildcard_match(pattern, name), "{pattern} {name}");
+        }
+    }
+
+    #[test]
+    fn test_directory_and_pattern_listing() {
+        let dir = std::env::temp_dir().join(format!("sensitive-rs-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.txt", "a.txt.gz", ".hidden.txt", "c.csv", "d.zst"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let names = |files: Vec<PathBuf>| -> Vec<String> { files.iter().map(|f| file_stem(f)).collect() };
+        assert_eq!(names(dictionary_files(&dir).unwrap()), ["a", "b"]);
+        assert_eq!(dictionary_files(&dir.join("*.csv")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(names(dictionary_files(&dir.join("*.txt*")).unwrap()), ["a", "b"]);
+        assert_eq!(dictionary_files(&dir.join("*.json")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(names(dictionary_files(&dir.join("?.txt")).unwrap()), ["b"]);
+        assert_eq!(names(dictionary_files(&dir.join(".h*")).unwrap()), [".hidden"]);
+        assert_eq!(dictionary_files(&dir.join("c.csv")).unwrap(), [dir.join("c.csv")]);
+        let error = dictionary_files(&dir.join("*").join("a.txt*")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(uncompressed_path(Path::new("words.csv.ZST")), Path::new("words.csv"));
+
+        std::fs::write(dir.join("padded.txt"), "赌博 \n\n  \n\t色情\n").unwrap();
+        assert_eq!(read_words(&dir.join("padded.txt")).unwrap(), ["赌博", "色情"]);
+
+        std::fs::write(dir.join("bad.txt"), b"\xe8\xb5\x8c\xe5\x8d\x9a\n\xff\n").unwrap();
+        let error = read_lines(&dir.join("bad.txt")).unwrap_err().to_string();
+        assert!(error.contains("bad.txt: line 2:"), "{error}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Comparing and combining dictionaries.
+
+use super::DictEntry;
+use super::write::{COLUMNS, line};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Which entry wins when several dictionaries given to [`merge_entries`] (or one
+/// dictionary given to it twice) have the same word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Precedence {
+    /// The first entry for a word is kept whole.
+    #[default]
+    First,
+    /// The last entry for a word is kept whole.
+    Last,
+    /// Fields set in a later entry override those of earlier ones; unset fields keep the
+    /// earlier value. Variant detection stays off once any entry turns it off.
+    Overlay,
+}
+
+/// Combine `dictionaries` into one sorted dictionary with one entry per word.
+///
+/// Words are trimmed and blank words dropped; entries with the same word are resolved by
+/// `precedence`, in the order the dictionaries (and their entries) are given.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{DictEntry, Precedence, merge_entries};
+///
+/// let mut bundled = DictEntry::new("赌博");
+/// bundled.severity = Some(2);
+/// let mut ours = DictEntry::new("赌博");
+/// ours.category = Some("gambling".into());
+///
+/// let merged = merge_entries([&[bundled][..], &[ours, DictEntry::new("色情")]], Precedence::Overlay);
+/// assert_eq!(merged.len(), 2);
+/// assert_eq!((merged[1].severity, merged[1].category.as_deref()), (Some(2), Some("gambling")));
+/// ```
+pub fn merge_entries<'a>(
+    dictionaries: impl IntoIterator<Item = &'a [DictEntry]>,
+    precedence: Precedence,
+) -> Vec<DictEntry> {
+    let mut merged: BTreeMap<String, DictEntry> = BTreeMap::new();
+    for entry in dictionaries.into_iter().flatten() {
+        let word = entry.word.trim();
+        if word.is_empty() {
+            continue;
+        }
+        let Some(kept) = merged.get_mut(word) else {
+            merged.insert(word.into(), DictEntry { word: word.into(), ..entry.clone() });
+            continue;
+        };
+        match precedence {
+            Precedence::First => {}
+            Precedence::Last => *kept = DictEntry { word: word.into(), ..entry.clone() },
+            Precedence::Overlay => {
+                fn overlay<T: Clone>(kept: &mut Option<T>, later: &Option<T>) {
+                    if later.is_some() {
+                        kept.clone_from(later);
+                    }
+                }
+                overlay(&mut kept.category, &entry.category);
+                overlay(&mut kept.severity, &entry.severity);
+                overlay(&mut kept.replacement, &entry.replacement);
+                overlay(&mut kept.priority, &entry.priority);
+                overlay(&mut kept.notes, &entry.notes);
+                kept.variants &= entry.variants;
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+/// The canonical form of a dictionary: words trimmed, blank words dropped, sorted, and
+/// only the first entry kept for each word.
+///
+/// Write it out with [`format_entries`](super::format_entries) to get a dictionary file
+/// that is the same for the same content, whatever the order and duplicates of the input.
+pub fn canonicalize(entries: &[DictEntry]) -> Vec<DictEntry> {
+    merge_entries([entries], Precedence::First)
+}
+
+/// The differences between two dictionaries, from [`diff_entries`]. Each list is sorted
+/// by word.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct DictDiff {
+    /// Entries only in the new dictionary.
+    pub added: Vec<DictEntry>,
+    /// Entries only in the old dictionary.
+    pub removed: Vec<DictEntry>,
+    /// Words in both whose metadata differs, as `(old, new)`.
+    pub changed: Vec<(DictEntry, DictEntry)>,
+}
+
+impl DictDiff {
+    /// Whether the dictionaries have the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// One line per difference: `+ entry` and `- entry` in the line format, and
+/// `~ word | field: old -> new | ...` for changed metadata (`-` for an unset field).
+impl fmt::Display for DictDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "+ {}", line(entry))?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {}", line(entry))?;
+        }
+        for (old, new) in &self.changed {
+            write!(f, "~ {}", line(&DictEntry::new(old.word.as_str())))?;
+            let (old, new) = (old.fields(), new.fields());
+            for name in COLUMNS {
+                let unset = if name == "variants" { "true" } else { "-" };
+                let value = |fields: &[(&str, String)]| {
+                    fields
+                        .iter()
+                        .find(|(field, _)| *field == name)
+                        .map_or(unset, |(_, value)| value.as_str())
+                        .to_owned()
+                };
+                let (before, after) = (value(&old), value(&new));
+                if before != after {
+                    write!(f, " | {name}: {before} -> {after}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two dictionaries by word, after [`canonicalize`]-ing both.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{DictFormat, diff_entries, parse_entries};
+///
+/// let old = parse_entries("赌博 | severity=2\n色情\n", DictFormat::Lines)?;
+/// let new = parse_entries("赌博 | severity=3\n诈骗\n", DictFormat::Lines)?;
+/// let diff = diff_entries(&old, &new);
+/// assert_eq!(diff.to_string(), "+ 诈骗\n- 色情\n~ 赌博 | severity: 2 -> 3\n");
+/// # Ok::<(), sensitive_rs::DictError>(())
+/// ```
+pub fn diff_entries(old: &[DictEntry], new: &[DictEntry]) -> DictDiff {
+    let mut remaining: BTreeMap<String, DictEntry> =
+        canonicalize(old).into_iter().map(|e| (e.word.clone(), e)).collect();
+    let mut diff = DictDiff::default();
+    for entry in canonicalize(new) {
+        match remaining.remove(&entry.word) {
+            None => diff.added.push(entry),
+            Some(before) if before != entry => diff.changed.push((before, entry)),
+            Some(_) => {}
+        }
+    }
+    diff.removed = remaining.into_values().collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(word: &str, severity: Option<u8>, category: Option<&str>) -> DictEntry {
+        let mut entry = DictEntry::new(word);
+        (entry.severity, entry.category) = (severity, category.map(String::from));
+        entry
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let base = [entry("赌博", Some(2), Some("gambling")), entry("色情 ", None, None), entry("", Some(1), None)];
+        let ours = [entry("赌博", Some(4), None), entry("诈骗", None, None)];
+        let merge = |precedence| merge_entries([&base[..], &ours[..]], precedence);
+
+        let words: Vec<_> = merge(Precedence::First).into_iter().map(|e| e.word).collect();
+        assert_eq!(words, ["色情", "诈骗", "赌博"]);
+        assert_eq!(merge(Precedence::First)[2], base[0]);
+        assert_eq!(merge(Precedence::Last)[2], ours[0]);
+        assert_eq!(merge(Precedence::Overlay)[2], entry("赌博", Some(4), Some("gambling")));
+        assert_eq!(
+            canonicalize(&[ours[1].clone(), ours[0].clone(), base[0].clone()]),
+            [ours[1].clone(), ours[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_each_change() {
+        let old = [entry("赌博", Some(2), None), entry("色情", None, None), entry("赌博", Some(9), None)];
+        let mut exact = entry("赌博", Some(2), Some("gambling"));
+        exact.variants = false;
+        let new = [exact, entry("诈骗", None, None)];
+
+        let diff = diff_entries(&old, &new);
+        assert_eq!(diff.added, [entry("诈骗", None, None)]);
+        assert_eq!(diff.removed, [entry("色情", None, None)]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.to_string(), "+ 诈骗\n- 色情\n~ 赌博 | category: - -> gambling | variants: true -> false\n");
+        assert!(diff_entries(&new, &new).is_empty());
+    }
+}
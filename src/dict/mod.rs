@@ -0,0 +1,599 @@
+//! Structured dictionary files.
+//!
+//! A [`DictEntry`] is a word plus optional metadata: category, severity, replacement
+//! text, priority, whether variants are detected, and notes. [`parse_entries`] reads
+//! entries in any [`DictFormat`]; [`Filter::load_entries`](crate::Filter::load_entries)
+//! picks the format from the file extension. Parse errors carry the 1-based line number.
+//!
+//! The line format (`.txt`) is one word per line with optional `key=value` fields after
+//! `|` and `#` comments, either on their own line or after whitespace:
+//!
+//! ```text
+//! # gambling
+//! 赌博
+//! 赌博机 | category=gambling | severity=3 | replace=[赌] # machines too
+//! 博彩   | priority=2 | variants=false | note=exact form only
+//! ```
+//!
+//! Trailing whitespace is never part of a word; a `#` or `|` inside a word is written
+//! `\#` or `\|`. CSV and TSV files start with a header naming their columns (`word` is
+//! required; the others are the field names above). JSON (feature `dict-json`) and TOML
+//! (feature `dict-toml`) documents hold a `words` list of entries, each a bare string or
+//! a table with the same field names; a JSON document may also be the list itself.
+
+mod files;
+mod merge;
+mod write;
+
+pub(crate) use files::{dictionary_files, file_stem, read_words};
+pub use merge::{DictDiff, Precedence, canonicalize, diff_entries, merge_entries};
+pub use write::format_entries;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// One dictionary word with its metadata.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{DictEntry, Filter};
+///
+/// let mut entry = DictEntry::new("赌博");
+/// entry.category = Some("gambling".into());
+/// entry.replacement = Some("[赌]".into());
+///
+/// let mut filter = Filter::new();
+/// filter.add_entries(&[entry]);
+/// assert_eq!(filter.category_of("赌博"), Some("gambling"));
+/// assert_eq!(filter.redact("含有赌博"), "含有[赌]");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "dict-json", feature = "dict-toml"), derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(any(feature = "dict-json", feature = "dict-toml"), serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct DictEntry {
+    /// The word.
+    pub word: String,
+    /// Category (see [`Filter::category_of`](crate::Filter::category_of)).
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub category: Option<String>,
+    /// Severity, higher is worse (see [`Filter::severity_of`](crate::Filter::severity_of)).
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub severity: Option<u8>,
+    /// Text that replaces the word in [`Filter::redact`](crate::Filter::redact).
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default, alias = "replace", skip_serializing_if = "Option::is_none")
+    )]
+    pub replacement: Option<String>,
+    /// Priority for [`MatchKind::Priority`](crate::MatchKind::Priority).
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub priority: Option<i32>,
+    /// Whether pinyin/shape variants of the word are detected (on by default).
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default = "enabled", skip_serializing_if = "is_enabled")
+    )]
+    pub variants: bool,
+    /// Free-form notes, kept for tooling and not used for matching.
+    #[cfg_attr(
+        any(feature = "dict-json", feature = "dict-toml"),
+        serde(default, alias = "note", skip_serializing_if = "Option::is_none")
+    )]
+    pub notes: Option<String>,
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+fn enabled() -> bool {
+    true
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+fn is_enabled(variants: &bool) -> bool {
+    *variants
+}
+
+impl DictEntry {
+    /// An entry for `word` without metadata.
+    pub fn new(word: impl Into<String>) -> Self {
+        Self {
+            word: word.into(),
+            category: None,
+            severity: None,
+            replacement: None,
+            priority: None,
+            variants: true,
+            notes: None,
+        }
+    }
+
+    /// The metadata fields that differ from [`DictEntry::new`], by name, in canonical order.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        let mut push = |name, value: Option<String>| fields.extend(value.map(|value| (name, value)));
+        push("category", self.category.clone());
+        push("severity", self.severity.map(|severity| severity.to_string()));
+        push("replacement", self.replacement.clone());
+        push("priority", self.priority.map(|priority| priority.to_string()));
+        push("variants", (!self.variants).then(|| "false".to_string()));
+        push("notes", self.notes.clone());
+        fields
+    }
+
+    /// Set the field `key` (a line-format / CSV column name) from its text form.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let text = || (!value.is_empty()).then(|| value.to_string());
+        match key {
+            "category" => self.category = text(),
+            "severity" => self.severity = parse_number(value, "severity")?,
+            "replace" | "replacement" => self.replacement = text(),
+            "priority" => self.priority = parse_number(value, "priority")?,
+            "variants" => {
+                self.variants = match value {
+                    "" | "true" => true,
+                    "false" => false,
+                    _ => return Err(format!("variants must be true or false, not `{value}`")),
+                }
+            }
+            "note" | "notes" => self.notes = text(),
+            "word" => self.word = value.to_string(),
+            _ => return Err(format!("unknown field `{key}`")),
+        }
+        Ok(())
+    }
+}
+
+fn parse_number<T: core::str::FromStr>(value: &str, field: &str) -> Result<Option<T>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse().map(Some).map_err(|_| format!("invalid {field} `{value}`"))
+}
+
+/// Dictionary file formats understood by [`parse_entries`].
+///
+/// Every format trims the words it reads. Blank lines and rows are skipped, but an entry
+/// whose word is empty once trimmed is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DictFormat {
+    /// One word per line with `|` fields and `#` comments.
+    Lines,
+    /// Comma-separated values with a header row; fields may be quoted.
+    Csv,
+    /// Tab-separated values with a header row.
+    Tsv,
+    /// A JSON document (feature `dict-json`).
+    #[cfg(feature = "dict-json")]
+    Json,
+    /// A TOML document (feature `dict-toml`).
+    #[cfg(feature = "dict-toml")]
+    Toml,
+}
+
+impl DictFormat {
+    /// The format of a file by its extension: `csv`, `tsv`, `json`, `toml`, and the line
+    /// format for anything else. A `.gz` or `.zst` suffix is looked through.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension =
+            files::uncompressed_path(path.as_ref()).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("csv") => Self::Csv,
+            Some("tsv") => Self::Tsv,
+            #[cfg(feature = "dict-json")]
+            Some("json") => Self::Json,
+            #[cfg(feature = "dict-toml")]
+            Some("toml") => Self::Toml,
+            _ => Self::Lines,
+        }
+    }
+}
+
+/// Why a dictionary could not be loaded.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DictError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The content is malformed.
+    Parse {
+        /// 1-based line of the problem.
+        line: usize,
+        /// What is wrong.
+        message: String,
+    },
+}
+
+impl DictError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        Self::Parse { line, message: message.into() }
+    }
+}
+
+impl core::fmt::Display for DictError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read dictionary: {e}"),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for DictError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for DictError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Parse dictionary `text` in `format`.
+///
+/// # Errors
+///
+/// [`DictError::Parse`] with the line of the first malformed entry.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{DictError, DictFormat, parse_entries};
+///
+/// let entries = parse_entries("word,severity\n赌博,3\n色情,\n", DictFormat::Csv)?;
+/// assert_eq!(entries[0].severity, Some(3));
+///
+/// let error = parse_entries("赌博 | severity=high", DictFormat::Lines).unwrap_err();
+/// assert!(matches!(error, DictError::Parse { line: 1, .. }));
+/// # Ok::<(), DictError>(())
+/// ```
+pub fn parse_entries(text: &str, format: DictFormat) -> Result<Vec<DictEntry>, DictError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    match format {
+        DictFormat::Lines => parse_lines(text),
+        DictFormat::Csv => parse_table(csv_records(text, ',')?),
+        DictFormat::Tsv => parse_table(
+            text.lines().enumerate().map(|(i, line)| (i + 1, line.split('\t').map(str::to_string).collect())).collect(),
+        ),
+        #[cfg(feature = "dict-json")]
+        DictFormat::Json => parse_json(text),
+        #[cfg(feature = "dict-toml")]
+        DictFormat::Toml => parse_toml(text),
+    }
+}
+
+/// Read and parse a dictionary file, choosing the format by [`DictFormat::from_path`].
+/// Gzip (feature `dict-gzip`) and zstd (feature `dict-zstd`) compressed files are
+/// decompressed, recognized by their content.
+///
+/// # Errors
+///
+/// [`DictError::Io`] if the file cannot be read, [`DictError::Parse`] if it is malformed.
+pub fn read_entries(path: impl AsRef<Path>) -> Result<Vec<DictEntry>, DictError> {
+    let path = path.as_ref();
+    let mut text = String::new();
+    files::open(path)?.read_to_string(&mut text)?;
+    parse_entries(&text, DictFormat::from_path(path))
+}
+
+/// Read the plain word lists `path` names, one word per line, as
+/// [`Filter::load_word_dict`](crate::Filter::load_word_dict) finds them: a single file
+/// whatever its extension, or the `.txt` files (possibly `.txt.gz` / `.txt.zst`) in a
+/// directory or matching a pattern such as `dict/*.txt`, in name order. Each file comes
+/// with its lines as written, so positions in it can be reported; `load_word_dict` trims
+/// them and skips blank ones.
+///
+/// # Errors
+///
+/// If a file cannot be read or decompressed or has a line that is not UTF-8, with the
+/// file and line in the message, or if a directory or pattern names no word lists.
+pub fn read_word_lists(path: impl AsRef<Path>) -> io::Result<Vec<(PathBuf, Vec<String>)>> {
+    let files = files::dictionary_files(path.as_ref())?;
+    files.into_iter().map(|file| files::read_lines(&file).map(|lines| (file, lines))).collect()
+}
+
+fn parse_lines(text: &str) -> Result<Vec<DictEntry>, DictError> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let fields = split_line(line);
+        let Some((word, fields)) = fields.split_first() else { continue };
+        if word.is_empty() {
+            if fields.is_empty() {
+                continue;
+            }
+            return Err(DictError::parse(i + 1, "fields without a word"));
+        }
+        let mut entry = DictEntry::new(word.as_str());
+        for field in fields {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| DictError::parse(i + 1, format!("expected key=value, found `{field}`")))?;
+            entry.set(key.trim(), value.trim()).map_err(|message| DictError::parse(i + 1, message))?;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Split a line at unescaped `|`, dropping a `#` comment and trimming each part. A line
+/// that is only a comment or blank has no parts.
+fn split_line(line: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    let mut after_space = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some('#' | '|' | '\\')) => {
+                parts.last_mut().unwrap().push(chars.next().unwrap());
+            }
+            '#' if after_space => break,
+            '|' => parts.push(String::new()),
+            _ => parts.last_mut().unwrap().push(c),
+        }
+        after_space = c.is_whitespace() || c == '|';
+    }
+    if parts.len() == 1 && parts[0].trim().is_empty() {
+        return Vec::new();
+    }
+    parts.iter().map(|part| part.trim().to_string()).collect()
+}
+
+/// Records of a CSV document as `(line, fields)`, following RFC 4180 quoting; a quoted
+/// field may span lines.
+fn csv_records(text: &str, separator: char) -> Result<Vec<(usize, Vec<String>)>, DictError> {
+    let mut records = Vec::new();
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = vec![String::new()];
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            let field = fields.last_mut().unwrap();
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' if quoted => quoted = false,
+                '"' if field.is_empty() => quoted = true,
+                '\n' if !quoted => {
+                    line += 1;
+                    break;
+                }
+                '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                c if c == separator && !quoted => fields.push(String::new()),
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+        }
+        if quoted {
+            return Err(DictError::parse(start_line, "unterminated quoted field"));
+        }
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+/// Entries from a header row naming the columns and one row per entry. Blank rows are skipped.
+fn parse_table(records: Vec<(usize, Vec<String>)>) -> Result<Vec<DictEntry>, DictError> {
+    let mut records = records.into_iter().filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()));
+    let Some((header_line, header)) = records.next() else { return Ok(Vec::new()) };
+    let columns: Vec<String> = header.iter().map(|name| name.trim().to_ascii_lowercase()).collect();
+    let word_column = columns
+        .iter()
+        .position(|name| name == "word")
+        .ok_or_else(|| DictError::parse(header_line, "header has no `word` column"))?;
+    let mut probe = DictEntry::new("");
+    for name in &columns {
+        probe.set(name, "").map_err(|message| DictError::parse(header_line, message))?;
+    }
+
+    let mut entries = Vec::new();
+    for (line, fields) in records {
+        if fields.len() > columns.len() {
+            return Err(DictError::parse(line, format!("{} fields, header has {}", fields.len(), columns.len())));
+        }
+        let word = fields.get(word_column).map_or("", |word| word.trim());
+        if word.is_empty() {
+            return Err(DictError::parse(line, "empty word"));
+        }
+        let mut entry = DictEntry::new(word);
+        for (name, value) in columns.iter().zip(&fields) {
+            if name != "word" {
+                entry.set(name, value.trim()).map_err(|message| DictError::parse(line, message))?;
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// A `words` list item: a bare word or an entry table.
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+enum Item {
+    Word(String),
+    Entry(DictEntry),
+}
+
+// By hand rather than `#[serde(untagged)]`, which would lose the position of errors
+// inside an entry.
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+impl<'de> serde::Deserialize<'de> for Item {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Item;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("a word or an entry table")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, word: &str) -> Result<Item, E> {
+                Ok(Item::Word(trimmed(word)?.to_string()))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Item, A::Error> {
+                let mut entry: DictEntry =
+                    serde::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+                entry.word = trimmed(&entry.word)?.to_string();
+                Ok(Item::Entry(entry))
+            }
+        }
+
+        /// `word` without surrounding whitespace, as in the line format; an error if that
+        /// leaves nothing. Raised here so the error carries the item's position.
+        fn trimmed<E: serde::de::Error>(word: &str) -> Result<&str, E> {
+            let word = word.trim();
+            if word.is_empty() { Err(E::custom("empty word")) } else { Ok(word) }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    words: Vec<Item>,
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+fn into_entries(items: Vec<Item>) -> Vec<DictEntry> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            Item::Word(word) => DictEntry::new(word),
+            Item::Entry(entry) => entry,
+        })
+        .collect()
+}
+
+#[cfg(feature = "dict-json")]
+fn parse_json(text: &str) -> Result<Vec<DictEntry>, DictError> {
+    let items = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_str(text).map(|document: Document| document.words)
+    };
+    items.map(into_entries).map_err(|e| DictError::parse(e.line().max(1), e.to_string()))
+}
+
+#[cfg(feature = "dict-toml")]
+fn parse_toml(text: &str) -> Result<Vec<DictEntry>, DictError> {
+    match toml::from_str::<Document>(text) {
+        Ok(document) => Ok(into_entries(document.words)),
+        Err(e) => {
+            let line = e.span().map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+            Err(DictError::parse(line, e.message()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_format() {
+        let text = "# header\n\n赌博  \n赌博机 | category=gambling | severity=3 | replace=[赌] # machines\n\
+                    C\\#  | variants=false|note=a, b\n色 情#not a comment\n";
+        let entries = parse_entries(text, DictFormat::Lines).unwrap();
+        let words: Vec<&str> = entries.iter().map(|e| e.word.as_str()).collect();
+        assert_eq!(words, ["赌博", "赌博机", "C#", "色 情#not a comment"]);
+        let machine = &entries[1];
+        assert_eq!(machine.category.as_deref(), Some("gambling"));
+        assert_eq!((machine.severity, machine.replacement.as_deref()), (Some(3), Some("[赌]")));
+        assert!(!entries[2].variants);
+        assert_eq!(entries[2].notes.as_deref(), Some("a, b"));
+
+        for (text, line) in [("a\nb | severity=x", 2), ("a\n\nb | colour=red", 3), ("a | priority", 1)] {
+            assert!(
+                matches!(parse_entries(text, DictFormat::Lines), Err(DictError::Parse { line: l, .. }) if l == line)
+            );
+        }
+    }
+
+    #[test]
+    fn test_csv_and_tsv() {
+        let csv = "word,category,notes\n赌博,gambling,\"multi\nline, quoted \"\"note\"\"\"\n色情,,\n";
+        let entries = parse_entries(csv, DictFormat::Csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].notes.as_deref(), Some("multi\nline, quoted \"note\""));
+        assert_eq!(entries[1].category, None);
+
+        let error = parse_entries("word,severity\n赌博,1\n\"色情\",2,3\n", DictFormat::Csv).unwrap_err();
+        assert!(matches!(error, DictError::Parse { line: 3, .. }));
+        let error = parse_entries("word\n\"赌博\n", DictFormat::Csv).unwrap_err();
+        assert!(matches!(error, DictError::Parse { line: 2, .. }));
+        assert!(matches!(parse_entries("name\n赌博\n", DictFormat::Csv), Err(DictError::Parse { line: 1, .. })));
+
+        let entries = parse_entries("word,notes\n  赌博 , x\n", DictFormat::Csv).unwrap();
+        assert_eq!(entries[0].word, "赌博");
+        assert!(matches!(
+            parse_entries("word,notes\n赌博,a\n\"  \",b\n", DictFormat::Csv),
+            Err(DictError::Parse { line: 3, .. })
+        ));
+
+        let tsv = "severity\tword\n2\t赌, 博\n";
+        assert_eq!(parse_entries(tsv, DictFormat::Tsv).unwrap()[0].word, "赌, 博");
+    }
+
+    #[cfg(feature = "dict-json")]
+    #[test]
+    fn test_json() {
+        let json = r#"{"words": ["色情", {"word": "赌博", "severity": 2, "replace": "[赌]"}]}"#;
+        let entries = parse_entries(json, DictFormat::Json).unwrap();
+        assert_eq!(entries[1].replacement.as_deref(), Some("[赌]"));
+        assert_eq!(parse_entries(r#"["赌博"]"#, DictFormat::Json).unwrap().len(), 1);
+        let error = parse_entries("[\n\"a\",\n{\"word\": 1}\n]", DictFormat::Json).unwrap_err();
+        assert!(matches!(error, DictError::Parse { line: 3 | 4, .. }));
+
+        let entries = parse_entries(r#"[" 赌博 ", {"word": "色情\t"}]"#, DictFormat::Json).unwrap();
+        assert_eq!([entries[0].word.as_str(), entries[1].word.as_str()], ["赌博", "色情"]);
+        for json in ["[\n\"a\",\n\"\"\n]", "[\n\"a\",\n\" \"]", "[\n\"a\",\n{\"word\": \"  \"}\n]"] {
+            let error = parse_entries(json, DictFormat::Json).unwrap_err();
+            assert!(matches!(error, DictError::Parse { line: 3 | 4, .. }), "{json}: {error}");
+        }
+    }
+
+    #[cfg(feature = "dict-toml")]
+    #[test]
+    fn test_toml() {
+        let toml = "[[words]]\nword = \"赌博\"\ncategory = \"gambling\"\n";
+        let entries = parse_entries(toml, DictFormat::Toml).unwrap();
+        assert_eq!(entries[0].category.as_deref(), Some("gambling"));
+        let error = parse_entries("[[words]]\nword = \"a\"\nseverity = \"high\"\n", DictFormat::Toml).unwrap_err();
+        assert!(matches!(error, DictError::Parse { line: 3, .. }), "{error}");
+
+        let entries = parse_entries("words = [\" 赌博\"]\n", DictFormat::Toml).unwrap();
+        assert_eq!(entries[0].word, "赌博");
+        for toml in ["words = [\n\"a\",\n\" \",\n]\n", "[[words]]\nword = \"a\"\n[[words]]\nword = \"\"\n"] {
+            let error = parse_entries(toml, DictFormat::Toml).unwrap_err();
+            assert!(matches!(error, DictError::Parse { line: 3 | 4, .. }), "{toml}: {error}");
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Writing entries back out as dictionary text.
+
+use super::{DictEntry, DictFormat};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Field names in the order they are written.
+pub(super) const COLUMNS: [&str; 6] = ["category", "severity", "replacement", "priority", "variants", "notes"];
+
+/// Write `entries`, in the given order, as a dictionary in `format`.
+///
+/// The output reads back with [`parse_entries`](super::parse_entries) into the same
+/// entries, except that line breaks inside a field become spaces in the line format and
+/// TSV (tabs too, for TSV), which cannot represent them. Only fields that are set are
+/// written; CSV and TSV get a column for every field set in any entry.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{DictEntry, DictFormat, format_entries, parse_entries};
+///
+/// let mut entry = DictEntry::new("C#");
+/// entry.severity = Some(2);
+/// let text = format_entries(&[DictEntry::new("赌博"), entry], DictFormat::Lines);
+/// assert_eq!(text, "赌博\nC\\# | severity=2\n");
+/// assert_eq!(parse_entries(&text, DictFormat::Lines).unwrap()[1].word, "C#");
+/// ```
+pub fn format_entries(entries: &[DictEntry], format: DictFormat) -> String {
+    match format {
+        DictFormat::Lines => entries.iter().map(|entry| line(entry) + "\n").collect(),
+        DictFormat::Csv => table(entries, ",", csv_field),
+        DictFormat::Tsv => table(entries, "\t", |value| value.replace(['\t', '\n', '\r'], " ")),
+        #[cfg(feature = "dict-json")]
+        DictFormat::Json => {
+            serde_json::to_string_pretty(&Document::new(entries)).expect("entries serialize to JSON") + "\n"
+        }
+        #[cfg(feature = "dict-toml")]
+        DictFormat::Toml => toml::to_string(&Document::new(entries)).expect("entries serialize to TOML"),
+    }
+}
+
+/// An entry in the line format, without a line break.
+pub(super) fn line(entry: &DictEntry) -> String {
+    let mut out = escape(&entry.word);
+    for (name, value) in entry.fields() {
+        out.push_str(" | ");
+        out.push_str(name);
+        out.push('=');
+        out.push_str(&escape(&value));
+    }
+    out
+}
+
+/// Escape the line format's special characters and flatten line breaks.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '#' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) { format!("\"{}\"", value.replace('"', "\"\"")) } else { value.into() }
+}
+
+/// A header row, then one row per entry, with columns for the fields in use.
+fn table(entries: &[DictEntry], separator: &str, field: impl Fn(&str) -> String) -> String {
+    let fields: Vec<_> = entries.iter().map(DictEntry::fields).collect();
+    let columns: Vec<&str> =
+        COLUMNS.into_iter().filter(|column| fields.iter().flatten().any(|(name, _)| name == column)).collect();
+
+    let mut out = String::from("word");
+    for column in &columns {
+        out.push_str(separator);
+        out.push_str(column);
+    }
+    out.push('\n');
+    for (entry, fields) in entries.iter().zip(&fields) {
+        out.push_str(&field(&entry.word));
+        for column in &columns {
+            out.push_str(separator);
+            if let Some((_, value)) = fields.iter().find(|(name, _)| name == column) {
+                out.push_str(&field(value));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `{"words": [...]}`, with entries without metadata written as bare words.
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+#[derive(serde::Serialize)]
+struct Document<'a> {
+    words: Vec<Item<'a>>,
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+impl<'a> Document<'a> {
+    fn new(entries: &'a [DictEntry]) -> Self {
+        let words = entries
+            .iter()
+            .map(|entry| if entry.fields().is_empty() { Item::Word(&entry.word) } else { Item::Entry(entry) })
+            .collect();
+        Self { words }
+    }
+}
+
+#[cfg(any(feature = "dict-json", feature = "dict-toml"))]
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum Item<'a> {
+    Word(&'a str),
+    Entry(&'a DictEntry),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_entries;
+
+    fn entries() -> Vec<DictEntry> {
+        let mut machine = DictEntry::new("赌博|机");
+        (machine.category, machine.severity, machine.variants) = (Some("gambling".into()), Some(3), false);
+        let mut quoted = DictEntry::new("色情");
+        quoted.notes = Some("a, \"b\" # c".into());
+        vec![DictEntry::new("C#"), machine, quoted]
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        let formats = [
+            DictFormat::Lines,
+            DictFormat::Csv,
+            DictFormat::Tsv,
+            #[cfg(feature = "dict-json")]
+            DictFormat::Json,
+            #[cfg(feature = "dict-toml")]
+            DictFormat::Toml,
+        ];
+        for format in formats {
+            let text = format_entries(&entries(), format);
+            assert_eq!(parse_entries(&text, format).unwrap(), entries(), "{format:?}:\n{text}");
+        }
+        assert_eq!(
+            format_entries(&entries(), DictFormat::Csv),
+            "word,category,severity,variants,notes\nC#,,,,\n赌博|机,gambling,3,false,\n色情,,,,\"a, \"\"b\"\" # c\"\n"
+        );
+    }
+}
@@ -10,11 +10,20 @@
 //!
 //! Use [`MultiPatternEngine::recommend_algorithm`] to preview the choice, or force one with
 //! [`MultiPatternEngine::rebuild_with_algorithm`].
+//!
+//! How overlapping dictionary entries are resolved is set by [`MatchKind`], independently of
+//! the algorithm: every algorithm returns the same matches for the same input.
 
+mod prebuilt;
 pub mod wumanber;
+pub use crate::engine::prebuilt::StaticDict;
 use crate::engine::wumanber::WuManber;
-use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
-use alloc::{string::String, string::ToString, sync::Arc, vec::Vec};
+use crate::replace::{self, ReplaceStrategy};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, Input};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::{string::String, string::ToString, sync::Arc, vec, vec::Vec};
+use hashbrown::{HashMap, HashSet};
 use regex::Regex;
 
 /// Supported matching algorithm types
@@ -53,13 +62,123 @@ impl core::fmt::Display for MatchAlgorithm {
     }
 }
 
+/// How overlapping dictionary entries are resolved into non-overlapping matches
+///
+/// Applied identically by every [`MatchAlgorithm`].
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{MatchKind, MultiPatternEngine};
+///
+/// let patterns = vec!["赌".to_string(), "赌博机".to_string(), "博机".to_string()];
+/// let mut engine = MultiPatternEngine::new(None, &patterns);
+/// assert_eq!(engine.find_all("赌博机"), ["赌博机"]);
+///
+/// engine.set_match_kind(MatchKind::LeftmostFirst);
+/// assert_eq!(engine.find_all("赌博机"), ["赌", "博机"]);
+///
+/// engine.set_match_kind(MatchKind::Priority);
+/// engine.set_priority("博机", 1);
+/// assert_eq!(engine.find_all("赌博机"), ["赌", "博机"]);
+/// engine.set_priority("赌博机", 2);
+/// assert_eq!(engine.find_all("赌博机"), ["赌博机"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The match starting first wins; among those starting at the same position, the longest.
+    #[default]
+    LeftmostLongest,
+    /// The match starting first wins; among those starting at the same position, the one
+    /// added to the dictionary first.
+    LeftmostFirst,
+    /// Higher [priority](MultiPatternEngine::set_priority) wins over any overlapping match,
+    /// wherever it starts; equal priorities fall back to leftmost-longest.
+    Priority,
+}
+
+/// Resolve overlapping `(start, end, key)` candidates into non-overlapping ones according
+/// to `kind`, sorted by start. `rank` gives a key's
+/// priority and its place in the dictionary (earlier added is lower). Among candidates
+/// with the same span, the first one given is kept.
+pub(crate) fn resolve_by_kind<T>(
+    kind: MatchKind,
+    mut candidates: Vec<(usize, usize, T)>,
+    rank: impl Fn(&T) -> (i32, usize),
+) -> Vec<(usize, usize, T)> {
+    match kind {
+        MatchKind::LeftmostLongest | MatchKind::LeftmostFirst => {
+            if kind == MatchKind::LeftmostLongest {
+                candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+            } else {
+                candidates.sort_by_key(|(start, _, key)| (*start, rank(key).1));
+            }
+            let mut kept = Vec::with_capacity(candidates.len());
+            let mut cursor = 0usize;
+            for c in candidates {
+                if c.0 >= cursor {
+                    cursor = c.1;
+                    kept.push(c);
+                }
+            }
+            kept
+        }
+        MatchKind::Priority => {
+            candidates.sort_by(|a, b| rank(&b.2).0.cmp(&rank(&a.2).0).then(a.0.cmp(&b.0)).then(b.1.cmp(&a.1)));
+            // Accepted spans keyed by start; a candidate is accepted if it overlaps
+            // neither its predecessor nor its successor.
+            let mut kept: BTreeMap<usize, (usize, T)> = BTreeMap::new();
+            for (start, end, key) in candidates {
+                let before = kept.range(..=start).next_back().is_some_and(|(_, (e, _))| *e > start);
+                let after = kept.range(start..).next().is_some_and(|(&s, _)| s < end);
+                if !before && !after {
+                    kept.insert(start, (end, key));
+                }
+            }
+            kept.into_iter().map(|(start, (end, key))| (start, end, key)).collect()
+        }
+    }
+}
+
 /// Multi-pattern matching engine
+#[derive(Clone)]
 pub struct MultiPatternEngine {
     algorithm: MatchAlgorithm,    // The matching algorithm currently used
     ac: Option<Arc<AhoCorasick>>, // Aho-Corasick Engine
     wm: Option<Arc<WuManber>>,    // Wu-Manber Engine
     regex_set: Option<Regex>,     // Regular Expression Engine
     patterns: Vec<String>,        // Store all modes
+    // Pattern -> id of its first copy; also answers `contains` for incremental updates
+    pattern_ids: HashMap<String, usize>,
+    max_len: usize,     // Byte length of the longest pattern
+    sorted: Vec<usize>, // Pattern ids in byte order of their patterns, for prefix lookups
+    overlapping: bool,  // Report nested/overlapping matches from find_all
+    // Standard-semantics automaton for overlapping search (AhoCorasick/Regex, overlapping mode
+    // or MatchKind::Priority only)
+    overlapping_ac: Option<Arc<AhoCorasick>>,
+    match_kind: MatchKind,
+    priorities: HashMap<String, i32>, // Pattern -> priority for MatchKind::Priority (default 0)
+    auto_algorithm: bool,             // Re-select the algorithm on rebuild (false once one is forced)
+    delta: Option<Delta>,             // Changes since the last full build (see `insert`/`remove`)
+    prebuilt: Option<&'static StaticDict>, // Automaton built at compile time, until the next full build
+}
+
+/// Patterns added and removed since the compiled engines were built.
+///
+/// The compiled engines still hold the old ("base") dictionary. While a delta is pending,
+/// matches come from overlapping candidates: the base automaton's, minus removed patterns,
+/// plus those of a small automaton over the added patterns, renumbered to ids in the current
+/// pattern list. Resolving them gives exactly what a full rebuild would.
+#[derive(Clone)]
+struct Delta {
+    /// Current id of each base pattern, `None` once removed.
+    base_ids: Vec<Option<usize>>,
+    /// Number of base patterns still present; `patterns[base_len..]` were added since.
+    base_len: usize,
+    /// Number of base patterns removed.
+    removed: usize,
+    /// Standard-semantics automaton over the added patterns.
+    added: Option<Arc<AhoCorasick>>,
 }
 
 impl core::fmt::Debug for MultiPatternEngine {
@@ -70,20 +189,40 @@ impl core::fmt::Debug for MultiPatternEngine {
             .field("has_ac", &self.ac.is_some())
             .field("has_wm", &self.wm.is_some())
             .field("has_regex", &self.regex_set.is_some())
+            .field("prebuilt", &self.prebuilt.is_some())
+            .field("overlapping", &self.overlapping)
+            .field("match_kind", &self.match_kind)
             .finish()
     }
 }
 
 impl Default for MultiPatternEngine {
     fn default() -> Self {
-        Self { algorithm: MatchAlgorithm::AhoCorasick, ac: None, wm: None, regex_set: None, patterns: Vec::new() }
+        Self {
+            algorithm: MatchAlgorithm::AhoCorasick,
+            ac: None,
+            wm: None,
+            regex_set: None,
+            patterns: Vec::new(),
+            pattern_ids: HashMap::new(),
+            max_len: 0,
+            sorted: Vec::new(),
+            overlapping: false,
+            overlapping_ac: None,
+            match_kind: MatchKind::LeftmostLongest,
+            priorities: HashMap::new(),
+            auto_algorithm: true,
+            delta: None,
+            prebuilt: None,
+        }
     }
 }
 
 impl MultiPatternEngine {
     /// Create a new engine and automatically select the algorithm based on the lexicon size
     ///
-    /// Pass `None` for `algorithm` to auto-select; pass [`Some`] to force a specific algorithm.
+    /// Pass `None` for `algorithm` to auto-select; pass [`Some`] to force a specific algorithm,
+    /// which is then kept across [`MultiPatternEngine::rebuild`].
     ///
     /// # Examples
     ///
@@ -95,21 +234,43 @@ impl MultiPatternEngine {
     /// assert_eq!(engine.find_first("含有赌博"), Some("赌博".to_string()));
     /// ```
     pub fn new(algorithm: Option<MatchAlgorithm>, patterns: &[String]) -> Self {
-        let algorithm = algorithm.unwrap_or_else(|| Self::recommend_algorithm(patterns.len()));
-        let mut engine = Self { algorithm, ..Default::default() };
+        let mut engine = match algorithm {
+            Some(algorithm) => Self { algorithm, auto_algorithm: false, ..Default::default() },
+            None => Self::default(),
+        };
 
         engine.rebuild(patterns);
         engine
     }
 
+    /// Create an engine over a dictionary compiled at build time
+    ///
+    /// The prebuilt automaton is used as is, whatever the match kind or overlapping mode.
+    /// The engine still keeps its own copy of every pattern, indexed by pattern and in sorted
+    /// order, which takes time and memory in proportion to the dictionary; only the
+    /// automaton is borrowed. Words inserted or removed later are matched alongside it as by
+    /// [`MultiPatternEngine::insert`]; a full rebuild (for example once those changes
+    /// accumulate) compiles the dictionary at runtime as usual.
+    pub fn from_static(dict: &'static StaticDict) -> Self {
+        let mut engine = Self {
+            algorithm: MatchAlgorithm::AhoCorasick,
+            patterns: dict.patterns().map(String::from).collect(),
+            prebuilt: Some(dict),
+            ..Default::default()
+        };
+        engine.index_patterns();
+        engine
+    }
+
     /// Rebuild the engine (called when the pattern is updated)
+    ///
+    /// The algorithm is re-selected for the new lexicon size unless one was forced.
     pub fn rebuild(&mut self, patterns: &[String]) {
         self.patterns = patterns.to_vec();
 
         // Reevaluate algorithm selection based on new thesaurus size
-        let recommended = Self::recommend_algorithm(patterns.len());
-        if self.algorithm != recommended {
-            self.algorithm = recommended;
+        if self.auto_algorithm {
+            self.algorithm = Self::recommend_algorithm(patterns.len());
         }
 
         self.build_engines();
@@ -129,9 +290,12 @@ impl MultiPatternEngine {
     }
 
     /// Force rebuild using the specified algorithm
+    ///
+    /// The algorithm is kept across later [`MultiPatternEngine::rebuild`] calls.
     pub fn rebuild_with_algorithm(&mut self, patterns: &[String], algorithm: MatchAlgorithm) {
         self.patterns = patterns.to_vec();
         self.algorithm = algorithm;
+        self.auto_algorithm = false;
         self.build_engines();
     }
 
@@ -141,15 +305,25 @@ impl MultiPatternEngine {
         self.ac = None;
         self.wm = None;
         self.regex_set = None;
+        self.pattern_ids.clear();
+        self.overlapping_ac = None;
+        self.delta = None;
+        self.prebuilt = None;
+        if !self.priorities.is_empty() {
+            let present: HashSet<&str> = self.patterns.iter().map(String::as_str).collect();
+            self.priorities.retain(|pattern, _| present.contains(pattern.as_str()));
+        }
 
         // Build the corresponding engine according to the selected algorithm
         match self.algorithm {
             MatchAlgorithm::AhoCorasick => {
                 if !self.patterns.is_empty() {
-                    match AhoCorasickBuilder::new()
-                        .match_kind(aho_corasick::MatchKind::LeftmostLongest)
-                        .build(&self.patterns)
-                    {
+                    let kind = match self.match_kind {
+                        MatchKind::LeftmostFirst => aho_corasick::MatchKind::LeftmostFirst,
+                        // Priority resolves from overlapping candidates, not this automaton.
+                        MatchKind::LeftmostLongest | MatchKind::Priority => aho_corasick::MatchKind::LeftmostLongest,
+                    };
+                    match AhoCorasickBuilder::new().match_kind(kind).build(&self.patterns) {
                         Ok(ac) => self.ac = Some(Arc::new(ac)),
                         Err(_) => {
                             // Fallback to WuManber if AhoCorasick build fails
@@ -166,7 +340,13 @@ impl MultiPatternEngine {
             }
             MatchAlgorithm::Regex => {
                 if !self.patterns.is_empty() {
-                    let escaped_patterns: Vec<String> = self.patterns.iter().map(|p| regex::escape(p)).collect();
+                    // Alternation is leftmost-first in dictionary order; trying longer patterns
+                    // first makes it leftmost-longest, the same resolution as the automaton.
+                    let mut ordered: Vec<&String> = self.patterns.iter().collect();
+                    if self.match_kind != MatchKind::LeftmostFirst {
+                        ordered.sort_by_key(|p| core::cmp::Reverse(p.len()));
+                    }
+                    let escaped_patterns: Vec<String> = ordered.into_iter().map(|p| regex::escape(p)).collect();
                     let pattern = escaped_patterns.join("|");
 
                     match Regex::new(&pattern) {
@@ -180,6 +360,336 @@ impl MultiPatternEngine {
                 }
             }
         }
+
+        self.index_patterns();
+    }
+
+    /// Map patterns to ids and build the overlapping automaton the algorithm needs.
+    fn index_patterns(&mut self) {
+        for (id, pattern) in self.patterns.iter().enumerate() {
+            self.pattern_ids.entry(pattern.clone()).or_insert(id);
+        }
+        self.max_len = self.patterns.iter().map(String::len).max().unwrap_or(0);
+        self.sorted = (0..self.patterns.len()).collect();
+        self.sorted.sort_unstable_by(|&a, &b| self.patterns[a].cmp(&self.patterns[b]));
+
+        // WuManber's and the prebuilt scans are already overlapping; the others need a second automaton.
+        if self.prebuilt.is_none()
+            && (self.overlapping || self.match_kind == MatchKind::Priority)
+            && self.algorithm != MatchAlgorithm::WuManber
+        {
+            self.overlapping_ac = Self::build_overlapping_ac(&self.patterns).map(Arc::new);
+        }
+    }
+
+    fn build_overlapping_ac(patterns: &[String]) -> Option<AhoCorasick> {
+        if patterns.is_empty() {
+            return None;
+        }
+        AhoCorasickBuilder::new().match_kind(aho_corasick::MatchKind::Standard).build(patterns).ok()
+    }
+
+    /// Append patterns without recompiling the whole dictionary
+    ///
+    /// Matching afterwards is exactly as after [`MultiPatternEngine::rebuild`] with the
+    /// patterns appended, but only a small automaton over the recently added patterns is
+    /// built; the compiled engines are rebuilt once enough changes have accumulated (or on
+    /// [`MultiPatternEngine::compact`]). WuManber dictionaries are small and are always
+    /// rebuilt, as is a dictionary whose size calls for another algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::MultiPatternEngine;
+    ///
+    /// let patterns: Vec<String> = (0..500).map(|i| format!("词{i}")).collect();
+    /// let mut engine = MultiPatternEngine::new(None, &patterns);
+    /// engine.insert(&["赌博".to_string()]);
+    /// engine.remove(&["词7".to_string()]);
+    /// assert_eq!(engine.find_all("词7赌博"), ["赌博"]);
+    /// ```
+    pub fn insert(&mut self, patterns: &[String]) {
+        if patterns.is_empty() {
+            return;
+        }
+        // Nothing compiled to keep: build everything at once.
+        if self.patterns.is_empty() || !self.can_update_in_place(self.patterns.len() + patterns.len()) {
+            let mut all = self.patterns.clone();
+            all.extend_from_slice(patterns);
+            self.rebuild(&all);
+            return;
+        }
+        self.begin_delta();
+        for pattern in patterns {
+            self.pattern_ids.entry(pattern.clone()).or_insert(self.patterns.len());
+            let at = self.sorted.partition_point(|&id| self.patterns[id] <= *pattern);
+            self.sorted.insert(at, self.patterns.len());
+            self.patterns.push(pattern.clone());
+            self.max_len = self.max_len.max(pattern.len());
+        }
+        self.update_delta(true);
+    }
+
+    /// Remove every copy of the given patterns without recompiling the whole dictionary
+    ///
+    /// The counterpart of [`MultiPatternEngine::insert`]: matching afterwards is exactly as
+    /// after [`MultiPatternEngine::rebuild`] without them. Removed base patterns are only
+    /// skipped until the next full build.
+    pub fn remove(&mut self, patterns: &[String]) {
+        let doomed: HashSet<&str> = patterns.iter().map(String::as_str).collect();
+        let remaining = self.patterns.iter().filter(|p| !doomed.contains(p.as_str())).count();
+        if remaining == self.patterns.len() {
+            return;
+        }
+        if !self.can_update_in_place(remaining) {
+            let kept: Vec<String> = self.patterns.iter().filter(|p| !doomed.contains(p.as_str())).cloned().collect();
+            self.rebuild(&kept);
+            return;
+        }
+        self.begin_delta();
+
+        // New id of every current pattern, `None` for removed ones.
+        let mut renumber = Vec::with_capacity(self.patterns.len());
+        let mut kept = Vec::with_capacity(remaining);
+        for pattern in self.patterns.drain(..) {
+            if doomed.contains(pattern.as_str()) {
+                renumber.push(None);
+            } else {
+                renumber.push(Some(kept.len()));
+                kept.push(pattern);
+            }
+        }
+        self.patterns = kept;
+        self.max_len = self.patterns.iter().map(String::len).max().unwrap_or(0);
+        self.sorted = self.sorted.iter().filter_map(|&id| renumber[id]).collect();
+        self.pattern_ids.retain(|pattern, _| !doomed.contains(pattern.as_str()));
+        for id in self.pattern_ids.values_mut() {
+            *id = renumber[*id].expect("kept pattern");
+        }
+        self.priorities.retain(|pattern, _| !doomed.contains(pattern.as_str()));
+
+        let delta = self.delta.as_mut().expect("delta started above");
+        let added_removed = renumber[delta.base_len..].iter().any(Option::is_none);
+        for id in &mut delta.base_ids {
+            *id = id.and_then(|id| renumber[id]);
+        }
+        let base_len = delta.base_ids.iter().flatten().count();
+        delta.removed += delta.base_len - base_len;
+        delta.base_len = base_len;
+        self.update_delta(added_removed);
+    }
+
+    /// Fold pending [`MultiPatternEngine::insert`]/[`MultiPatternEngine::remove`] changes
+    /// into the compiled engines now, rather than when enough have accumulated.
+    pub fn compact(&mut self) {
+        if self.delta.is_some() {
+            self.build_engines();
+        }
+    }
+
+    /// Whether a dictionary of `len` patterns can be reached by updating in place.
+    fn can_update_in_place(&self, len: usize) -> bool {
+        self.algorithm != MatchAlgorithm::WuManber
+            && (!self.auto_algorithm || Self::recommend_algorithm(len) == self.algorithm)
+    }
+
+    fn begin_delta(&mut self) {
+        if self.delta.is_some() {
+            return;
+        }
+        // Base candidates come from the overlapping automaton, built once per full build.
+        if self.overlapping_ac.is_none() && self.prebuilt.is_none() {
+            self.overlapping_ac = Self::build_overlapping_ac(&self.patterns).map(Arc::new);
+        }
+        let base_len = self.patterns.len();
+        self.delta = Some(Delta { base_ids: (0..base_len).map(Some).collect(), base_len, removed: 0, added: None });
+    }
+
+    /// Rebuild the automaton over the added patterns if they changed, or everything once
+    /// the delta outgrows its budget.
+    fn update_delta(&mut self, added_changed: bool) {
+        let delta = self.delta.as_mut().expect("delta started");
+        let added = &self.patterns[delta.base_len..];
+        // Matching a delta costs about as much as matching the base, so its size is bounded.
+        let budget = delta.base_ids.len().isqrt().max(16) * 4;
+        if added.len() + delta.removed > budget {
+            self.build_engines();
+        } else if added_changed {
+            delta.added = Self::build_overlapping_ac(added).map(Arc::new);
+        }
+    }
+
+    /// Whether the compiled engine's own leftmost search gives the resolved matches.
+    fn native_search(&self) -> bool {
+        self.delta.is_none()
+            && self.prebuilt.is_none()
+            && matches!(self.algorithm, MatchAlgorithm::AhoCorasick | MatchAlgorithm::Regex)
+            && matches!(self.match_kind, MatchKind::LeftmostLongest | MatchKind::LeftmostFirst)
+    }
+
+    /// Enable or disable overlapping mode
+    ///
+    /// When enabled, [`MultiPatternEngine::find_all`] reports every dictionary entry that
+    /// occurs in the text, including entries nested inside or overlapping longer ones
+    /// (`赌博` inside `赌博机`). AhoCorasick and Regex build an extra automaton for this,
+    /// so the mode is off by default. Replacement and [`MultiPatternEngine::find_iter`]
+    /// keep their non-overlapping resolution either way.
+    pub fn set_overlapping(&mut self, overlapping: bool) {
+        if self.overlapping != overlapping {
+            self.overlapping = overlapping;
+            // The prebuilt automaton serves every mode.
+            if self.prebuilt.is_none() {
+                self.build_engines();
+            }
+        }
+    }
+
+    /// Whether overlapping mode is enabled (see [`MultiPatternEngine::set_overlapping`])
+    pub fn is_overlapping(&self) -> bool {
+        self.overlapping
+    }
+
+    /// Set how overlapping dictionary entries are resolved (see [`MatchKind`])
+    pub fn set_match_kind(&mut self, match_kind: MatchKind) {
+        if self.match_kind != match_kind {
+            self.match_kind = match_kind;
+            if self.prebuilt.is_none() {
+                self.build_engines();
+            }
+        }
+    }
+
+    /// The current [`MatchKind`]
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    /// Set a pattern's priority for [`MatchKind::Priority`] (higher wins; default 0)
+    ///
+    /// Only patterns in the dictionary keep a priority: it is dropped when the pattern
+    /// is removed by a rebuild.
+    pub fn set_priority(&mut self, pattern: &str, priority: i32) {
+        if priority == 0 {
+            self.priorities.remove(pattern);
+        } else if self.contains(pattern) {
+            self.priorities.insert(pattern.to_string(), priority);
+        }
+    }
+
+    /// A pattern's priority (0 unless set with [`MultiPatternEngine::set_priority`])
+    pub fn priority(&self, pattern: &str) -> i32 {
+        self.priorities.get(pattern).copied().unwrap_or(0)
+    }
+
+    /// Every occurrence of every pattern, including nested and overlapping ones
+    ///
+    /// Sorted by start, then end. The result is the same on every algorithm. Works
+    /// whether or not overlapping mode is enabled, but without it AhoCorasick and Regex
+    /// build a temporary automaton on each call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::MultiPatternEngine;
+    ///
+    /// let patterns = vec!["赌博".to_string(), "赌博机".to_string(), "博机".to_string()];
+    /// let engine = MultiPatternEngine::new(None, &patterns);
+    /// let found: Vec<_> = engine.find_overlapping("赌博机").into_iter().map(|m| m.pattern).collect();
+    /// assert_eq!(found, ["赌博", "赌博机", "博机"]);
+    /// ```
+    pub fn find_overlapping(&self, text: &str) -> Vec<MatchInfo> {
+        self.candidates(text).into_iter().map(|(start, end, _)| self.match_info(text, start, end)).collect()
+    }
+
+    /// Every occurrence as `(start, end, pattern_id)`, sorted by start then end, one per span.
+    fn candidates(&self, text: &str) -> Vec<(usize, usize, usize)> {
+        let mut matches: Vec<(usize, usize, usize)> = match self.algorithm {
+            MatchAlgorithm::WuManber => match &self.wm {
+                Some(wm) => wm
+                    .find_matches(text)
+                    .into_iter()
+                    .filter_map(|m| Some((m.start, m.end, self.pattern_id(text.get(m.start..m.end)?)?)))
+                    .collect(),
+                None => Vec::new(),
+            },
+            MatchAlgorithm::AhoCorasick | MatchAlgorithm::Regex if self.delta.is_some() => {
+                let delta = self.delta.as_ref().expect("checked above");
+                let base_ids = |(start, end, id): (usize, usize, usize)| Some((start, end, delta.base_ids[id]?));
+                let prebuilt =
+                    self.prebuilt.iter().flat_map(|dict| dict.find_overlapping_iter(text).filter_map(base_ids));
+                let base = self.overlapping_ac.iter().flat_map(|ac| {
+                    ac.find_overlapping_iter(text)
+                        .filter_map(|mat| base_ids((mat.start(), mat.end(), mat.pattern().as_usize())))
+                });
+                let added = delta.added.iter().flat_map(|ac| {
+                    ac.find_overlapping_iter(text)
+                        .map(|mat| (mat.start(), mat.end(), delta.base_len + mat.pattern().as_usize()))
+                });
+                prebuilt.chain(base).chain(added).collect()
+            }
+            MatchAlgorithm::AhoCorasick | MatchAlgorithm::Regex if self.prebuilt.is_some() => {
+                self.prebuilt.iter().flat_map(|dict| dict.find_overlapping_iter(text)).collect()
+            }
+            MatchAlgorithm::AhoCorasick | MatchAlgorithm::Regex => {
+                let temporary;
+                let ac = match &self.overlapping_ac {
+                    Some(ac) => ac.as_ref(),
+                    None => match Self::build_overlapping_ac(&self.patterns) {
+                        Some(ac) => {
+                            temporary = ac;
+                            &temporary
+                        }
+                        None => return Vec::new(),
+                    },
+                };
+                ac.find_overlapping_iter(text).map(|mat| (mat.start(), mat.end(), mat.pattern().as_usize())).collect()
+            }
+        };
+        // Duplicate dictionary entries would otherwise be reported once per copy; the
+        // sort puts the first-added copy first so it is the one kept.
+        matches.sort_unstable();
+        matches.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+        matches
+    }
+
+    /// Resolve overlapping candidates (sorted as by [`MultiPatternEngine::candidates`])
+    /// into non-overlapping matches according to the match kind, sorted by start.
+    fn resolve(&self, candidates: Vec<(usize, usize, usize)>) -> Vec<(usize, usize, usize)> {
+        resolve_by_kind(self.match_kind, candidates, |&id| (self.priority(&self.patterns[id]), id))
+    }
+
+    /// Non-overlapping `(start, end, pattern_id)` matches according to the match kind.
+    fn resolved(&self, text: &str) -> Vec<(usize, usize, usize)> {
+        if self.patterns.is_empty() {
+            return Vec::new();
+        }
+        if self.native_search() {
+            // The automaton / alternation was built with this kind: use its own search.
+            self.find_iter(text).map(|m| (m.start, m.end, m.pattern_id)).collect()
+        } else {
+            self.resolve(self.candidates(text))
+        }
+    }
+
+    /// Matches among only the patterns `keep` accepts, as if the others were not in the
+    /// dictionary: every occurrence with `overlapping`, else resolved by the match kind.
+    pub(crate) fn find_where(&self, text: &str, overlapping: bool, keep: impl Fn(&str) -> bool) -> Vec<MatchRef<'_>> {
+        if self.patterns.is_empty() {
+            return Vec::new();
+        }
+        let mut candidates = self.candidates(text);
+        candidates.retain(|&(_, _, id)| keep(&self.patterns[id]));
+        if !overlapping {
+            candidates = self.resolve(candidates);
+        }
+        candidates
+            .into_iter()
+            .map(|(start, end, id)| MatchRef { pattern: &self.patterns[id], pattern_id: id, start, end })
+            .collect()
+    }
+
+    fn match_info(&self, text: &str, start: usize, end: usize) -> MatchInfo {
+        MatchInfo { pattern: text[start..end].to_string(), start, end }
     }
 
     /// Get the currently used algorithm
@@ -192,6 +702,25 @@ impl MultiPatternEngine {
         &self.patterns
     }
 
+    /// Length in bytes of the longest pattern, 0 for an empty dictionary
+    pub fn max_pattern_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Byte length of the longest suffix of `text` that a longer pattern begins with: the
+    /// part of `text` that more input could still turn into a match.
+    pub(crate) fn unfinished_suffix(&self, text: &str) -> usize {
+        let from = text.len().saturating_sub(self.max_len.saturating_sub(1));
+        (from..text.len())
+            .filter(|&i| text.is_char_boundary(i))
+            .find(|&i| {
+                let prefix = &text[i..];
+                let at = self.sorted.partition_point(|&id| self.patterns[id].as_str() <= prefix);
+                self.sorted.get(at).is_some_and(|&id| self.patterns[id].starts_with(prefix))
+            })
+            .map_or(0, |i| text.len() - i)
+    }
+
     /// Find the first match
     ///
     /// # Examples
@@ -205,52 +734,79 @@ impl MultiPatternEngine {
     /// assert_eq!(engine.find_first("正常文本"), None);
     /// ```
     pub fn find_first(&self, text: &str) -> Option<String> {
-        match self.algorithm {
-            MatchAlgorithm::AhoCorasick => {
-                self.ac.as_ref()?.find(text).map(|mat| text[mat.start()..mat.end()].to_string())
+        if self.patterns.is_empty() {
+            return None;
+        }
+        if self.native_search() {
+            return self.find_iter(text).next().map(|m| m.pattern.to_string());
+        }
+        self.first_resolved(text).map(|(_, _, id)| self.patterns[id].clone())
+    }
+
+    /// The first match [`MultiPatternEngine::resolved`] would return, from a prefix of `text`.
+    ///
+    /// Candidates fall into clusters of overlapping spans, and no match kind lets one
+    /// cluster affect another. The first cluster is complete once the scanned prefix
+    /// reaches a pattern length past its end, so only that much text is searched, in
+    /// doubling windows.
+    fn first_resolved(&self, text: &str) -> Option<(usize, usize, usize)> {
+        let mut window = self.max_len.saturating_mul(4).max(256);
+        loop {
+            let mut end = window.min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
             }
-            MatchAlgorithm::WuManber => {
-                // Use the search_string method to return directly to String
-                self.wm.as_ref()?.search_string(text)
+            let candidates = self.candidates(&text[..end]);
+            if let Some(&(first, mut reach, _)) = candidates.first() {
+                let cluster: Vec<_> = candidates
+                    .into_iter()
+                    .take_while(|&(start, stop, _)| {
+                        let joined = start == first || start < reach;
+                        if joined {
+                            reach = reach.max(stop);
+                        }
+                        joined
+                    })
+                    .collect();
+                // A candidate running past the window starts within a pattern length of its end.
+                if end == text.len() || reach + self.max_len <= end {
+                    return self.resolve(cluster).into_iter().next();
+                }
+            } else if end == text.len() {
+                return None;
             }
-            MatchAlgorithm::Regex => self.regex_set.as_ref()?.find(text).map(|mat| mat.as_str().to_string()),
+            window = window.saturating_mul(2);
         }
     }
 
     /// Replace all matches with optimized performance
+    ///
+    /// Matches are resolved by the [`MatchKind`] as everywhere else, but the replacement
+    /// unit is algorithm-specific: one `replacement` per match for AhoCorasick/Regex, one
+    /// per matched character for WuManber. Use [`MultiPatternEngine::replace_with_strategy`]
+    /// for output that is identical on every algorithm.
     pub fn replace_all(&self, text: &str, replacement: &str) -> String {
-        match self.algorithm {
-            MatchAlgorithm::AhoCorasick => {
-                if let Some(ac) = &self.ac {
-                    ac.replace_all(text, &[replacement]).to_string()
+        let per_char = self.algorithm == MatchAlgorithm::WuManber && !replacement.is_empty();
+        let mask = replacement.chars().next().unwrap_or('*');
+        replace::rebuild(
+            text,
+            self.resolved(text).into_iter().map(|(start, end, _)| {
+                let replacement = if per_char {
+                    Cow::Owned(core::iter::repeat_n(mask, text[start..end].chars().count()).collect())
                 } else {
-                    text.to_string()
-                }
-            }
-            MatchAlgorithm::WuManber => {
-                if let Some(wm) = &self.wm {
-                    if replacement.is_empty() {
-                        wm.remove_all(text)
-                    } else {
-                        let repl_char = replacement.chars().next().unwrap_or('*');
-                        wm.replace_all(text, repl_char)
-                    }
-                } else {
-                    text.to_string()
-                }
-            }
-            MatchAlgorithm::Regex => {
-                if let Some(regex) = &self.regex_set {
-                    regex.replace_all(text, replacement).to_string()
-                } else {
-                    text.to_string()
-                }
-            }
-        }
+                    Cow::Borrowed(replacement)
+                };
+                (start..end, replacement)
+            }),
+        )
     }
 
     /// Find all matches
     ///
+    /// Non-overlapping matches resolved by the [`MatchKind`], or every occurrence in
+    /// overlapping mode (see [`MultiPatternEngine::set_overlapping`]). The result is the
+    /// same on every algorithm.
+    ///
     /// # Examples
     ///
     /// ```
@@ -262,76 +818,89 @@ impl MultiPatternEngine {
     /// assert_eq!(matches.len(), 2);
     /// ```
     pub fn find_all(&self, text: &str) -> Vec<String> {
-        match self.algorithm {
-            MatchAlgorithm::AhoCorasick => {
-                if let Some(ac) = &self.ac {
-                    ac.find_iter(text).map(|mat| text[mat.start()..mat.end()].to_string()).collect()
-                } else {
-                    Vec::new()
-                }
-            }
-            MatchAlgorithm::WuManber => {
-                if let Some(wm) = &self.wm {
-                    wm.search_all_strings(text)
-                } else {
-                    Vec::new()
-                }
-            }
-            MatchAlgorithm::Regex => {
-                if let Some(regex) = &self.regex_set {
-                    regex.find_iter(text).map(|mat| mat.as_str().to_string()).collect()
-                } else {
-                    Vec::new()
-                }
-            }
+        if self.overlapping {
+            return self.find_overlapping(text).into_iter().map(|m| m.pattern).collect();
         }
+        self.resolved(text).into_iter().map(|(start, end, _)| text[start..end].to_string()).collect()
     }
 
     /// Get detailed match information
+    ///
+    /// Non-overlapping matches resolved by the [`MatchKind`], sorted by start. The result
+    /// is the same on every algorithm.
     pub fn find_matches_with_positions(&self, text: &str) -> Vec<MatchInfo> {
-        match self.algorithm {
-            MatchAlgorithm::AhoCorasick => {
-                if let Some(ac) = &self.ac {
-                    ac.find_iter(text)
-                        .map(|mat| MatchInfo {
-                            pattern: text[mat.start()..mat.end()].to_string(),
-                            start: mat.start(),
-                            end: mat.end(),
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            }
-            MatchAlgorithm::WuManber => {
-                if let Some(wm) = &self.wm {
-                    wm.find_matches(text)
-                        .into_iter()
-                        .filter_map(|m| {
-                            let pattern = text.get(m.start..m.end)?;
-                            Some(MatchInfo { pattern: pattern.to_string(), start: m.start, end: m.end })
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            }
-            MatchAlgorithm::Regex => {
-                if let Some(regex) = &self.regex_set {
-                    regex
-                        .find_iter(text)
-                        .map(|mat| MatchInfo { pattern: mat.as_str().to_string(), start: mat.start(), end: mat.end() })
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            }
-        }
+        self.resolved(text).into_iter().map(|(start, end, _)| self.match_info(text, start, end)).collect()
+    }
+
+    /// Replace every match according to `strategy`
+    ///
+    /// Unlike [`MultiPatternEngine::replace_all`], the output is the same whichever
+    /// algorithm is selected. The engine has no category information, so
+    /// [`ReplaceStrategy::Category`] always uses its fallback here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{MatchAlgorithm, MultiPatternEngine, ReplaceStrategy};
+    ///
+    /// let patterns = vec!["赌博".to_string()];
+    /// let mut engine = MultiPatternEngine::default();
+    /// for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+    ///     engine.rebuild_with_algorithm(&patterns, algorithm);
+    ///     assert_eq!(engine.replace_with_strategy("含有赌博", &ReplaceStrategy::Mask('*')), "含有**");
+    /// }
+    /// ```
+    pub fn replace_with_strategy(&self, text: &str, strategy: &ReplaceStrategy) -> String {
+        let matches = self.find_matches_with_positions(text);
+        replace::rebuild(text, matches.iter().map(|m| (m.start..m.end, strategy.replacement(&m.pattern, None))))
+    }
+
+    /// Iterate lazily over matches without allocating per match
+    ///
+    /// Yields the same matches as [`MultiPatternEngine::find_matches_with_positions`], as
+    /// [`MatchRef`]s that borrow the pattern from the engine. AhoCorasick and Regex
+    /// locate each match only when it is requested, so stopping early skips the rest of
+    /// the scan; WuManber (small dictionaries) and [`MatchKind::Priority`] locate all
+    /// matches on the first call to `next` and yield them from there. `text` may be
+    /// borrowed or owned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::MultiPatternEngine;
+    ///
+    /// let patterns = vec!["赌博".to_string(), "色情".to_string()];
+    /// let engine = MultiPatternEngine::new(None, &patterns);
+    /// let m = engine.find_iter("含有色情").next().unwrap();
+    /// assert_eq!((m.pattern, m.pattern_id, m.start, m.end), ("色情", 1, 6, 12));
+    /// ```
+    pub fn find_iter<T: AsRef<str>>(&self, text: T) -> FindIter<'_, T> {
+        FindIter { engine: self, text, cursor: 0, pending: None }
+    }
+
+    /// Whether `pattern` is in the dictionary
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.pattern_ids.contains_key(pattern)
+    }
+
+    /// Pattern id of a matched slice (WuManber/Regex, which report text rather than ids).
+    pub(crate) fn pattern_id(&self, matched: &str) -> Option<usize> {
+        self.pattern_ids.get(matched).copied()
     }
 
     /// Check if text contains any patterns
     pub fn contains_any(&self, text: &str) -> bool {
-        self.find_first(text).is_some()
+        if self.delta.is_some() {
+            return !self.candidates(text).is_empty();
+        }
+        if let Some(dict) = self.prebuilt {
+            return dict.is_match(text);
+        }
+        match self.algorithm {
+            MatchAlgorithm::WuManber => self.wm.as_ref().is_some_and(|wm| wm.search(text).is_some()),
+            MatchAlgorithm::AhoCorasick => self.ac.as_ref().is_some_and(|ac| ac.is_match(text)),
+            MatchAlgorithm::Regex => self.regex_set.as_ref().is_some_and(|regex| regex.is_match(text)),
+        }
     }
 
     /// Get engine statistics
@@ -362,6 +931,133 @@ impl MultiPatternEngine {
     }
 }
 
+#[cfg(feature = "std")]
+impl MultiPatternEngine {
+    /// Append the dictionary, settings and (for Wu-Manber) compiled tables to a snapshot payload.
+    pub(crate) fn encode(&self, e: &mut crate::snapshot::Encoder) {
+        e.u8(match self.algorithm {
+            MatchAlgorithm::AhoCorasick => 0,
+            MatchAlgorithm::WuManber => 1,
+            MatchAlgorithm::Regex => 2,
+        });
+        e.bool(self.auto_algorithm);
+        e.u8(match self.match_kind {
+            MatchKind::LeftmostLongest => 0,
+            MatchKind::LeftmostFirst => 1,
+            MatchKind::Priority => 2,
+        });
+        e.bool(self.overlapping);
+        e.strs(&self.patterns);
+        let mut priorities: Vec<_> = self.priorities.iter().collect();
+        priorities.sort_unstable();
+        e.usize(priorities.len());
+        for (pattern, &priority) in priorities {
+            e.str(pattern);
+            e.i32(priority);
+        }
+        // Tables built before pending incremental changes are stale; those are rebuilt on load.
+        match self.wm.as_deref().filter(|_| self.delta.is_none()) {
+            Some(wm) => {
+                e.bool(true);
+                wm.encode(e);
+            }
+            None => e.bool(false),
+        }
+    }
+
+    /// Read an engine written by [`MultiPatternEngine::encode`], compiling only the automata
+    /// that were not stored.
+    pub(crate) fn decode(d: &mut crate::snapshot::Decoder<'_>) -> Result<Self, crate::snapshot::SnapshotError> {
+        use crate::snapshot::SnapshotError::Corrupt;
+
+        let algorithm = match d.u8()? {
+            0 => MatchAlgorithm::AhoCorasick,
+            1 => MatchAlgorithm::WuManber,
+            2 => MatchAlgorithm::Regex,
+            _ => return Err(Corrupt("unknown algorithm")),
+        };
+        let auto_algorithm = d.bool()?;
+        let match_kind = match d.u8()? {
+            0 => MatchKind::LeftmostLongest,
+            1 => MatchKind::LeftmostFirst,
+            2 => MatchKind::Priority,
+            _ => return Err(Corrupt("unknown match kind")),
+        };
+        let overlapping = d.bool()?;
+        let patterns = d.strings()?;
+        let mut priorities = HashMap::new();
+        for _ in 0..d.len()? {
+            priorities.insert(d.string()?, d.i32()?);
+        }
+        let wm = if d.bool()? { WuManber::decode(d, patterns.clone())? } else { None };
+
+        let mut engine =
+            Self { algorithm, auto_algorithm, match_kind, overlapping, patterns, priorities, ..Self::default() };
+        match wm {
+            Some(wm) if algorithm == MatchAlgorithm::WuManber => {
+                engine.wm = Some(Arc::new(wm));
+                engine.index_patterns();
+            }
+            _ => engine.build_engines(),
+        }
+        Ok(engine)
+    }
+}
+
+/// A match that borrows its pattern from the engine, yielded by [`MultiPatternEngine::find_iter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchRef<'a> {
+    /// The matched pattern.
+    pub pattern: &'a str,
+    /// Index of the pattern in [`MultiPatternEngine::get_patterns`].
+    pub pattern_id: usize,
+    /// Byte offset where the match starts.
+    pub start: usize,
+    /// Byte offset where the match ends (exclusive).
+    pub end: usize,
+}
+
+/// Lazy iterator over resolved matches, created by [`MultiPatternEngine::find_iter`]
+#[derive(Debug)]
+pub struct FindIter<'a, T> {
+    engine: &'a MultiPatternEngine,
+    text: T,
+    cursor: usize,
+    // WuManber has no "next match from here" primitive, and priority resolution and
+    // pending incremental changes need every candidate, so those matches are located once
+    pending: Option<vec::IntoIter<(usize, usize, usize)>>,
+}
+
+impl<'a, T: AsRef<str>> Iterator for FindIter<'a, T> {
+    type Item = MatchRef<'a>;
+
+    fn next(&mut self) -> Option<MatchRef<'a>> {
+        let engine = self.engine;
+        let text = self.text.as_ref();
+        if self.cursor > text.len() {
+            return None;
+        }
+        let (pattern_id, start, end) = match engine.algorithm {
+            MatchAlgorithm::AhoCorasick if engine.native_search() => {
+                let mat = engine.ac.as_ref()?.find(Input::new(text).span(self.cursor..text.len()))?;
+                (mat.pattern().as_usize(), mat.start(), mat.end())
+            }
+            MatchAlgorithm::Regex if engine.native_search() => {
+                let mat = engine.regex_set.as_ref()?.find_at(text, self.cursor)?;
+                (engine.pattern_id(mat.as_str())?, mat.start(), mat.end())
+            }
+            _ => {
+                let pending = self.pending.get_or_insert_with(|| engine.resolved(text).into_iter());
+                let (start, end, pattern_id) = pending.next()?;
+                (pattern_id, start, end)
+            }
+        };
+        // An empty pattern matches without consuming input; step past it so iteration ends.
+        self.cursor = if end > start { end } else { end + text[end..].chars().next().map_or(1, char::len_utf8) };
+        Some(MatchRef { pattern: engine.patterns[pattern_id].as_str(), pattern_id, start, end })
+    }
+}
+
 /// Match information with position details
 #[derive(Debug, Clone)]
 pub struct MatchInfo {
@@ -519,6 +1215,134 @@ mod tests {
         assert_eq!(engine.find_all("含有赌博").len(), 1);
     }
 
+    #[test]
+    fn test_engine_regex_alternation_is_leftmost_longest() {
+        let patterns = vec!["赌".to_string(), "赌博".to_string(), "赌博机".to_string()];
+        let mut engine = MultiPatternEngine::default();
+        engine.rebuild_with_algorithm(&patterns, MatchAlgorithm::Regex);
+        assert_eq!(engine.find_all("赌博机"), vec!["赌博机".to_string()]);
+    }
+
+    #[test]
+    fn test_engine_replace_with_strategy_uniform() {
+        let patterns = vec!["赌".to_string(), "赌博".to_string(), "赌博机".to_string()];
+        let mut engine = MultiPatternEngine::default();
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            engine.rebuild_with_algorithm(&patterns, algorithm);
+            let out = engine.replace_with_strategy("赌博机和赌", &ReplaceStrategy::Token("[x]".to_string()));
+            assert_eq!(out, "[x]和[x]", "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn test_engine_find_iter_matches_leftmost_longest_on_every_algorithm() {
+        let patterns: Vec<String> = ["赌", "赌博", "赌博机", "色情"].iter().map(|s| s.to_string()).collect();
+        let text = "赌博机和色情还有赌";
+        let mut engine = MultiPatternEngine::default();
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            engine.rebuild_with_algorithm(&patterns, algorithm);
+            let lazy: Vec<(usize, usize, usize)> =
+                engine.find_iter(text).map(|m| (m.pattern_id, m.start, m.end)).collect();
+            let eager: Vec<(usize, usize, usize)> = engine
+                .find_matches_with_positions(text)
+                .iter()
+                .map(|m| (patterns.iter().position(|p| *p == m.pattern).unwrap(), m.start, m.end))
+                .collect();
+            assert_eq!(lazy, eager, "{algorithm}");
+            assert_eq!(lazy.len(), 3, "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn test_engine_overlapping_same_on_every_algorithm() {
+        let patterns: Vec<String> = ["赌", "赌博", "赌博机", "博机", "色情"].iter().map(|s| s.to_string()).collect();
+        let text = "赌博机和色情";
+        let mut engine = MultiPatternEngine::default();
+        engine.set_overlapping(true);
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            engine.rebuild_with_algorithm(&patterns, algorithm);
+            let spans: Vec<(usize, usize)> = engine.find_overlapping(text).iter().map(|m| (m.start, m.end)).collect();
+            assert_eq!(spans, vec![(0, 3), (0, 6), (0, 9), (3, 9), (12, 18)], "{algorithm}");
+            let mut all = engine.find_all(text);
+            all.sort();
+            assert_eq!(all, vec!["博机", "色情", "赌", "赌博", "赌博机"], "{algorithm}");
+        }
+
+        // Off by default: nested entries are resolved away.
+        engine.set_overlapping(false);
+        assert_eq!(engine.find_all(text), vec!["赌博机", "色情"]);
+        // find_overlapping still works without the mode.
+        assert_eq!(engine.find_overlapping(text).len(), 5);
+    }
+
+    #[test]
+    fn test_engine_match_kinds_same_on_every_algorithm() {
+        // Duplicates and nested/overlapping entries; "博" is added twice.
+        let patterns: Vec<String> =
+            ["博", "赌", "赌博机", "赌博", "博机", "机器", "博"].iter().map(|s| s.to_string()).collect();
+        let texts = ["赌博机器", "赌赌博博机机器", "无", "博赌博机"];
+        let mut engine = MultiPatternEngine::default();
+        for kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst, MatchKind::Priority] {
+            let mut expected: Option<Vec<Vec<(usize, usize, usize)>>> = None;
+            for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+                engine.rebuild_with_algorithm(&patterns, algorithm);
+                engine.set_match_kind(kind);
+                engine.set_priority("博机", 3);
+                let got: Vec<Vec<(usize, usize, usize)>> = texts
+                    .iter()
+                    .map(|text| engine.find_iter(*text).map(|m| (m.start, m.end, m.pattern_id)).collect())
+                    .collect();
+                for (text, matches) in texts.iter().zip(&got) {
+                    let positions: Vec<(usize, usize)> =
+                        engine.find_matches_with_positions(text).iter().map(|m| (m.start, m.end)).collect();
+                    assert_eq!(positions, matches.iter().map(|m| (m.0, m.1)).collect::<Vec<_>>());
+                }
+                match &expected {
+                    Some(first) => assert_eq!(&got, first, "{algorithm} {kind:?}"),
+                    None => expected = Some(got),
+                }
+            }
+        }
+        assert_eq!(engine.priority("博机"), 3);
+        engine.rebuild(&patterns[..2]);
+        assert_eq!(engine.priority("博机"), 0);
+    }
+
+    #[test]
+    fn test_engine_find_iter_stops_early() {
+        let engine = engine_with(&["赌博"]);
+        let text = "赌博".repeat(1000);
+        assert_eq!(engine.find_iter(text.as_str()).take(3).count(), 3);
+        // Owned text works too.
+        assert_eq!(engine.find_iter(text).count(), 1000);
+    }
+
+    #[test]
+    fn test_engine_find_first_matches_find_iter() {
+        let patterns: Vec<String> = ["赌", "赌博机", "博机器", "机器人"].iter().map(|s| s.to_string()).collect();
+        // Matches before, across and far beyond the first search window.
+        let texts = [
+            "赌博机器人".to_string(),
+            format!("{}赌博机器人", "无".repeat(85)),
+            format!("{}赌博机器人", "无".repeat(5000)),
+            "无".repeat(5000),
+        ];
+        let mut engine = MultiPatternEngine::default();
+        for kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst, MatchKind::Priority] {
+            for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+                engine.rebuild_with_algorithm(&patterns, algorithm);
+                engine.set_match_kind(kind);
+                engine.set_priority("机器人", 2);
+                engine.set_priority("博机器", 1);
+                for text in &texts {
+                    let expected = engine.find_iter(text.as_str()).next().map(|m| m.pattern.to_string());
+                    assert_eq!(engine.find_first(text), expected, "{algorithm} {kind:?}");
+                }
+            }
+        }
+        assert_eq!(engine.find_first("赌博机器人"), Some("赌".to_string()));
+    }
+
     #[test]
     fn test_engine_find_matches_with_positions_wumanber() {
         // Regression for the WuManber find_matches multi-byte panic (now fixed):
@@ -531,4 +1355,73 @@ mod tests {
         assert_eq!(matches[0].pattern, "赌博");
         assert_eq!(&text[matches[0].start..matches[0].end], "赌博");
     }
+    #[test]
+    fn test_engine_incremental_updates_match_full_rebuild() {
+        let base: Vec<String> = (0..150).map(|i| format!("词{i}")).collect();
+        let steps: [(bool, &[&str]); 6] = [
+            (true, &["赌博", "赌博机"]),
+            (false, &["词1", "词12"]),
+            (true, &["博机", "词1", "赌"]),
+            (false, &["赌博机", "词140"]),
+            (true, &["赌博机", "赌博"]),
+            (false, &["赌"]),
+        ];
+        let text = "词1词12赌博机词140赌博 词7赌";
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::Regex] {
+            for kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst, MatchKind::Priority] {
+                let rebuilt = |patterns: &[String]| {
+                    let mut engine = MultiPatternEngine::new(Some(algorithm), patterns);
+                    engine.set_match_kind(kind);
+                    engine.set_priority("博机", 5);
+                    engine
+                };
+                let mut engine = MultiPatternEngine::new(Some(algorithm), &base);
+                engine.set_match_kind(kind);
+                let mut patterns = base.clone();
+                for (add, words) in steps {
+                    let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+                    if add {
+                        engine.insert(&words);
+                        patterns.extend(words);
+                    } else {
+                        engine.remove(&words);
+                        patterns.retain(|p| !words.contains(p));
+                    }
+                    engine.set_priority("博机", 5);
+                    assert!(engine.delta.is_some(), "{algorithm} {kind:?}");
+
+                    let rebuilt = rebuilt(&patterns);
+                    assert_eq!(engine.get_patterns(), rebuilt.get_patterns());
+                    let spans = |e: &MultiPatternEngine| {
+                        e.find_iter(text).map(|m| (m.pattern_id, m.start, m.end)).collect::<Vec<_>>()
+                    };
+                    assert_eq!(spans(&engine), spans(&rebuilt), "{algorithm} {kind:?}");
+                    let all = |e: &MultiPatternEngine| (e.find_overlapping(text).len(), e.contains_any("赌博"));
+                    assert_eq!(all(&engine), all(&rebuilt), "{algorithm} {kind:?}");
+                }
+                engine.compact();
+                assert!(engine.delta.is_none());
+                assert_eq!(engine.find_all(text), rebuilt(&patterns).find_all(text));
+            }
+        }
+    }
+
+    #[test]
+    fn test_engine_delta_is_folded_in_when_large() {
+        let base: Vec<String> = (0..400).map(|i| format!("词{i}")).collect();
+        let mut engine = MultiPatternEngine::new(None, &base);
+        for i in 0..200 {
+            engine.insert(&[format!("新{i}")]);
+            if let Some(delta) = &engine.delta {
+                let pending = engine.patterns.len() - delta.base_len + delta.removed;
+                assert!(pending <= 4 * delta.base_ids.len().isqrt(), "{pending} changes pending");
+            }
+        }
+        assert_eq!(engine.find_all("新199词399").len(), 2);
+        // Crossing a size where another algorithm is recommended switches algorithm as a rebuild would.
+        let mut engine = engine_with(&["赌博"; 100]);
+        engine.insert(&["色情".to_string()]);
+        assert_eq!(engine.current_algorithm(), MatchAlgorithm::AhoCorasick);
+        assert!(engine.delta.is_none());
+    }
 }
@@ -0,0 +1,137 @@
+//! Dictionaries compiled at build time.
+//!
+//! A [`StaticDict`] is an Aho-Corasick automaton over the bytes of the dictionary's match
+//! forms, laid out in `static` arrays by the `codegen` build-script helper. It needs no
+//! construction at runtime and no allocator to scan, so it also serves `no_std` targets.
+
+/// Fields per automaton node in [`StaticDict`]'s `nodes` table.
+const NODE_STRIDE: usize = 5;
+
+/// A dictionary whose matching automaton was built at compile time.
+///
+/// Generated by `sensitive_rs::codegen` (feature `codegen`) in a build script and included
+/// with `include!`; construct a filter from it with
+/// [`Filter::from_static`](crate::Filter::from_static).
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("dict.rs");
+///     sensitive_rs::codegen::write_static_dict("dict/words.txt", out, "WORDS").unwrap();
+/// }
+///
+/// // src/lib.rs
+/// include!(concat!(env!("OUT_DIR"), "/dict.rs"));
+/// let filter = sensitive_rs::Filter::from_static(&WORDS);
+/// ```
+#[derive(Debug)]
+pub struct StaticDict {
+    /// Every match form, concatenated.
+    text: &'static str,
+    /// End of each match form in `text`; the id of a form is its index.
+    ends: &'static [u32],
+    /// Per node: first edge, edge count, failure node, first output, output count.
+    nodes: &'static [u32],
+    /// Edge labels, sorted within each node.
+    edge_bytes: &'static [u8],
+    /// Edge targets, parallel to `edge_bytes`.
+    edge_targets: &'static [u32],
+    /// Ids of the forms ending at each node, including those reached by failure links.
+    outputs: &'static [u32],
+    /// Pinyin key and the id of a form with that pinyin.
+    pinyin: &'static [(&'static str, u32)],
+    /// Pinyin of every Han character in the dictionary.
+    char_pinyin: &'static [(char, &'static str)],
+}
+
+impl StaticDict {
+    /// Assemble a dictionary from generated tables. Only for code written by `codegen`.
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub const fn from_parts(
+        text: &'static str,
+        ends: &'static [u32],
+        nodes: &'static [u32],
+        edge_bytes: &'static [u8],
+        edge_targets: &'static [u32],
+        outputs: &'static [u32],
+        pinyin: &'static [(&'static str, u32)],
+        char_pinyin: &'static [(char, &'static str)],
+    ) -> Self {
+        Self { text, ends, nodes, edge_bytes, edge_targets, outputs, pinyin, char_pinyin }
+    }
+
+    /// Number of match forms.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Whether the dictionary is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// The match form with id `id`.
+    ///
+    /// # Panics
+    ///
+    /// If `id >= self.len()`.
+    pub fn pattern(&self, id: usize) -> &'static str {
+        let start = if id == 0 { 0 } else { self.ends[id - 1] as usize };
+        &self.text[start..self.ends[id] as usize]
+    }
+
+    /// Every match form, in id order.
+    pub fn patterns(&self) -> impl Iterator<Item = &'static str> + '_ {
+        (0..self.len()).map(|id| self.pattern(id))
+    }
+
+    /// Whether any form occurs in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find_overlapping_iter(text).next().is_some()
+    }
+
+    /// Every occurrence of every form as `(start, end, id)`, in order of end.
+    pub fn find_overlapping_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        let mut state = 0;
+        text.bytes().enumerate().flat_map(move |(i, byte)| {
+            state = self.next_state(state, byte);
+            self.node_outputs(state).iter().map(move |&id| {
+                let id = id as usize;
+                (i + 1 - self.pattern(id).len(), i + 1, id)
+            })
+        })
+    }
+
+    pub(crate) fn pinyin_entries(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+        self.pinyin.iter().map(|&(key, id)| (key, self.pattern(id as usize)))
+    }
+
+    pub(crate) fn char_pinyin(&self) -> &'static [(char, &'static str)] {
+        self.char_pinyin
+    }
+
+    fn node(&self, state: usize) -> &'static [u32] {
+        &self.nodes[state * NODE_STRIDE..(state + 1) * NODE_STRIDE]
+    }
+
+    fn node_outputs(&self, state: usize) -> &'static [u32] {
+        let node = self.node(state);
+        &self.outputs[node[3] as usize..(node[3] + node[4]) as usize]
+    }
+
+    fn next_state(&self, mut state: usize, byte: u8) -> usize {
+        loop {
+            let node = self.node(state);
+            let first = node[0] as usize;
+            let labels = &self.edge_bytes[first..first + node[1] as usize];
+            if let Ok(edge) = labels.binary_search(&byte) {
+                return self.edge_targets[first + edge] as usize;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = node[2] as usize;
+        }
+    }
+}
@@ -719,6 +719,94 @@ impl WuManber {
     }
 }
 
+#[cfg(feature = "std")]
+impl WuManber {
+    /// Hashed by the snapshot writer and reader: the tables are only reusable if the hasher
+    /// of the loading build agrees with the one that built them.
+    const HASH_PROBE: &'static str = "赌博";
+
+    /// Append the tables to a snapshot payload.
+    pub(crate) fn encode(&self, e: &mut crate::snapshot::Encoder) {
+        e.u64(Self::calculate_hash_fast(Self::HASH_PROBE));
+        e.u8(match self.space_handling {
+            SpaceHandling::Strict => 0,
+            SpaceHandling::IgnoreSpaces => 1,
+            SpaceHandling::NormalizeSpaces => 2,
+        });
+        e.usize(self.min_len);
+        e.usize(self.block_size);
+        e.strs(&self.patterns.iter().map(|p| p.as_str()).collect::<Vec<_>>());
+        let mut shifts: Vec<_> = self.shift_table.iter().collect();
+        shifts.sort_unstable();
+        e.usize(shifts.len());
+        for (&hash, &shift) in shifts {
+            e.u64(hash);
+            e.usize(shift);
+        }
+        let mut buckets: Vec<_> = self.hash_table.iter().collect();
+        buckets.sort_unstable();
+        e.usize(buckets.len());
+        for (&hash, ids) in buckets {
+            e.u64(hash);
+            e.usize(ids.len());
+            ids.iter().for_each(|&id| e.usize(id));
+        }
+    }
+
+    /// Read tables written by [`WuManber::encode`] for `original_patterns`. `Ok(None)` if
+    /// they were hashed differently and have to be rebuilt.
+    pub(crate) fn decode(
+        d: &mut crate::snapshot::Decoder<'_>,
+        original_patterns: Vec<String>,
+    ) -> Result<Option<Self>, crate::snapshot::SnapshotError> {
+        use crate::snapshot::SnapshotError::Corrupt;
+
+        let probe = d.u64()?;
+        let space_handling = match d.u8()? {
+            0 => SpaceHandling::Strict,
+            1 => SpaceHandling::IgnoreSpaces,
+            2 => SpaceHandling::NormalizeSpaces,
+            _ => return Err(Corrupt("unknown space handling")),
+        };
+        let min_len = d.usize()?;
+        let block_size = d.usize()?;
+        let patterns: Vec<Arc<String>> = d.strings()?.into_iter().map(Arc::new).collect();
+        if !patterns.is_empty() && (block_size == 0 || block_size > min_len)
+            || patterns.iter().any(|p| p.chars().count() < min_len)
+        {
+            return Err(Corrupt("inconsistent Wu-Manber block size"));
+        }
+        let mut shift_table = HashMap::new();
+        for _ in 0..d.len()? {
+            shift_table.insert(d.u64()?, d.usize()?);
+        }
+        let mut hash_table = HashMap::new();
+        for _ in 0..d.len()? {
+            let hash = d.u64()?;
+            let ids = (0..d.len()?).map(|_| d.usize()).collect::<Result<SmallVec<_>, _>>()?;
+            if ids.iter().any(|&id| id >= patterns.len()) {
+                return Err(Corrupt("Wu-Manber pattern id out of range"));
+            }
+            hash_table.insert(hash, ids);
+        }
+
+        if probe != Self::calculate_hash_fast(Self::HASH_PROBE) {
+            return Ok(None);
+        }
+        let pattern_set = patterns.iter().cloned().collect();
+        Ok(Some(Self {
+            patterns,
+            original_patterns,
+            pattern_set,
+            min_len,
+            block_size,
+            shift_table,
+            hash_table,
+            space_handling,
+        }))
+    }
+}
+
 /// Match result with byte positions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Match {
@@ -2,57 +2,226 @@
 //!
 //! [`Filter`] is the primary entry point: load a dictionary with [`Filter::add_word`] /
 //! [`Filter::add_words`] / [`Filter::load_word_dict`], then query with [`Filter::find_all`]
-//! (all matches), [`Filter::find_in`] or [`Filter::find_first_match`] (first match),
-//! [`Filter::replace`] (mask), or [`Filter::filter`] (remove). Input text is first cleaned of
-//! noise via a configurable regex, then matched exactly against the dictionary, and finally
-//! checked for pinyin/shape variants.
-
-use crate::engine::{MatchAlgorithm, MatchInfo, MultiPatternEngine};
+//! (all matches), [`Filter::find_iter`] (lazy spans), [`Filter::find_in`] or [`Filter::find_first_match`] (first match),
+//! [`Filter::replace`] / [`Filter::replace_with_strategy`] (mask), or [`Filter::filter`] (remove).
+//! Input text is first cleaned of noise via a configurable regex, then matched exactly against
+//! the dictionary, and finally checked for pinyin/shape variants.
+
+use crate::builder::FilterBuilder;
+#[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+use crate::builtin::Builtin;
+use crate::engine::{MatchAlgorithm, MatchInfo, MatchKind, MatchRef, MultiPatternEngine, StaticDict};
+use crate::highlight::Markup;
+use crate::layer::Layers;
+use crate::lint::DictionaryReport;
+use crate::replace::{self, ReplaceStrategy};
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec;
 use alloc::vec::Vec;
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
 use regex::Regex;
 
+#[cfg(feature = "std")]
+use crate::dict::{self, DictEntry, DictError};
+#[cfg(feature = "std")]
+use crate::snapshot::{self, Decoder, Encoder, SnapshotError};
+#[cfg(feature = "async-io")]
+use crate::stream::{AsyncStreamScanner, ReaderChunks};
+#[cfg(feature = "std")]
+use crate::stream::{Scanner, StreamScanner};
 #[cfg(feature = "std")]
 use crate::variant::VariantDetector;
+use alloc::sync::Arc;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 #[cfg(feature = "std")]
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead, Read},
     path::Path,
 };
 #[cfg(feature = "std")]
-use {alloc::sync::Arc, lru::LruCache, std::num::NonZero, std::sync::Mutex};
+use {lru::LruCache, std::num::NonZero, std::sync::Mutex};
 
 /// Advanced sensitive word filter with variant detection
+///
+/// `Filter` is `Send + Sync` and cheap to clone: clones share the compiled dictionary
+/// (engine, variant data and categories) and the result cache through `Arc`s. A clone that
+/// changes its dictionary gets its own copy at that point, leaving the others untouched.
+/// The noise pattern, [`Filter::redact`]'s replacement strategy and the other options
+/// belong to each clone, so clones can differ in them without duplicating the automaton.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, ReplaceStrategy};
+///
+/// let mut filter = Filter::new();
+/// filter.add_word("赌博");
+///
+/// let mut tokens = filter.clone(); // shares the compiled dictionary
+/// tokens.set_replace_strategy(ReplaceStrategy::Token("[censored]".into()));
+///
+/// assert_eq!(filter.redact("赌博"), "**");
+/// assert_eq!(tokens.redact("赌博"), "[censored]");
+/// ```
+#[derive(Clone)]
 pub struct Filter {
+    compiled: Arc<Compiled>,           // Dictionary state, shared between clones until changed
+    noise: Regex,                      // Noise processing rules
+    replace_strategy: ReplaceStrategy, // Used by `redact`
+    #[cfg(feature = "std")]
+    cache: Arc<Mutex<LruCache<String, Vec<String>>>>, // find_all results for this dictionary and noise
+    #[cfg(feature = "parallel")]
+    parallel_threshold: usize, // find_all scans cleaned text longer than this in parallel
+    fold_whitespace: bool,             // Also match words containing spaces with the spaces removed
+    disabled_layers: BTreeSet<String>, // Layers left out of matching by this filter
+}
+
+/// The compiled dictionary of a [`Filter`], shared by its clones.
+#[derive(Clone)]
+struct Compiled {
     engine: MultiPatternEngine, // Multi-pattern matching engine
     #[cfg(feature = "std")]
     variant_detector: VariantDetector, // Variation detector (pinyin/shape)
-    noise: Regex,               // Noise processing rules
-    #[cfg(feature = "std")]
-    cache: Arc<Mutex<LruCache<String, Vec<String>>>>,
+    categories: HashMap<String, String>, // Matched form -> category name
+    replacements: HashMap<String, String>, // Matched form -> replacement text for `redact`
+    severities: HashMap<String, u8>, // Matched form -> severity
+    layers: Layers,             // Named word groups and their match forms
 }
 
-/// A sensitive-word match found by [`Filter::find_first_match`].
+/// Characters stripped from the input before matching: everything except word
+/// characters, whitespace and CJK ideographs.
+pub(crate) const DEFAULT_NOISE: &str = r"[^\w\s\u4e00-\u9fff]";
+/// Results cached by [`Filter::find_all`].
+pub(crate) const DEFAULT_CACHE_CAPACITY: usize = 1000;
+/// Cleaned text length, in bytes, above which [`Filter::find_all`] scans in parallel.
+pub(crate) const DEFAULT_PARALLEL_THRESHOLD: usize = 1000;
+
+/// A sensitive-word match found by [`Filter::find_first_match`] or passed to
+/// [`Filter::replace_with`].
 ///
 /// `word` is the matched word in its dictionary form; `is_variant` is `true` when
 /// the match came from pinyin/shape variant detection rather than an exact hit.
+/// `start..end` is the byte span of the match in the text passed by the caller, so it
+/// also covers any noise characters stripped from inside the word.
+///
+/// New fields may be added in minor releases; construct one with [`Match::new`].
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Match {
     /// The matched sensitive word, in dictionary form.
     pub word: String,
     /// `true` if matched via a pinyin/shape variant rather than an exact hit.
     pub is_variant: bool,
+    /// Byte offset where the match starts in the input text.
+    pub start: usize,
+    /// Byte offset where the match ends (exclusive) in the input text.
+    pub end: usize,
+    /// The layer the word came from (see [`Filter::add_layer`]); `None` for words added
+    /// outside layers.
+    pub layer: Option<String>,
+}
+
+impl Match {
+    /// A match of `word` at `start..end`, outside any layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, Match};
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    /// assert_eq!(filter.find_first_match("含有赌博"), Some(Match::new("赌博", false, 6, 12)));
+    /// ```
+    pub fn new(word: impl Into<String>, is_variant: bool, start: usize, end: usize) -> Self {
+        Self { word: word.into(), is_variant, start, end, layer: None }
+    }
+}
+
+/// Noise-stripped text plus what is needed to map its byte offsets back to the input.
+pub(crate) struct CleanText<'t> {
+    pub(crate) text: Cow<'t, str>,
+    offsets: OffsetMap,
+}
+
+impl CleanText<'_> {
+    /// Map a byte span of the clean text to the corresponding span of the input.
+    pub(crate) fn input_span(&self, start: usize, end: usize) -> (usize, usize) {
+        self.offsets.input_span(start, end)
+    }
+
+    /// The byte ranges of the input the clean text is made of, in order.
+    #[cfg(feature = "std")]
+    pub(crate) fn input_runs(&self) -> Vec<core::ops::Range<usize>> {
+        let runs = &self.offsets.runs;
+        if runs.is_empty() {
+            return (!self.text.is_empty()).then(|| 0..self.text.len()).into_iter().collect();
+        }
+        let ends = runs.iter().skip(1).map(|&(clean, _)| clean).chain([self.text.len()]);
+        runs.iter().zip(ends).map(|(&(clean, input), end)| input..input + end - clean).collect()
+    }
+}
+
+/// Maps byte offsets of noise-stripped text back to the input.
+struct OffsetMap {
+    /// `(clean_offset, input_offset)` at the start of every kept run. Empty when nothing
+    /// was stripped, in which case both offsets are the same.
+    runs: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    fn input_span(&self, start: usize, end: usize) -> (usize, usize) {
+        if self.runs.is_empty() {
+            return (start, end);
+        }
+        // The start belongs to the run it falls in; the end to the run it closes, so
+        // noise trailing the match is not included.
+        let s = self.runs.partition_point(|&(clean, _)| clean <= start).saturating_sub(1);
+        let e = self.runs.partition_point(|&(clean, _)| clean < end).saturating_sub(1);
+        (self.runs[s].1 + start - self.runs[s].0, self.runs[e].1 + end - self.runs[e].0)
+    }
+}
+
+/// A match over the clean text: its span, whether it is a variant, and the word.
+pub(crate) type Candidate = (core::ops::Range<usize>, bool, String);
+
+/// Resolve `exact` matches, already non-overlapping, together with `variants`: the
+/// variants that fit in the gaps between exact matches are kept, leftmost-longest, so a
+/// variant never displaces an exact match. Both lists are sorted by start, then longest
+/// first, and so is the result; `span` gives a match's span.
+pub(crate) fn fill_gaps<T>(
+    mut exact: Vec<T>,
+    variants: Vec<T>,
+    span: impl Fn(&T) -> &core::ops::Range<usize>,
+) -> Vec<T> {
+    let mut filled = Vec::new();
+    let mut cursor = 0usize;
+    for variant in variants {
+        let range = span(&variant);
+        let next = exact.partition_point(|e| span(e).end <= range.start);
+        if range.start >= cursor && exact.get(next).is_none_or(|e| span(e).start >= range.end) {
+            cursor = range.end;
+            filled.push(variant);
+        }
+    }
+    if !filled.is_empty() {
+        exact.extend(filled);
+        exact.sort_by_key(|m| span(m).start);
+    }
+    exact
 }
 
 impl core::fmt::Debug for Filter {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("Filter").field("engine", &self.engine).field("noise", &self.noise).finish_non_exhaustive()
+        f.debug_struct("Filter")
+            .field("engine", &self.compiled.engine)
+            .field("noise", &self.noise)
+            .field("replace_strategy", &self.replace_strategy)
+            .finish_non_exhaustive()
     }
 }
 
@@ -69,29 +238,195 @@ impl Filter {
     /// assert_eq!(filter.find_in("含有赌博"), (true, "赌博".to_string()));
     /// ```
     pub fn new() -> Self {
+        Self::configured(&FilterBuilder::new(), Regex::new(DEFAULT_NOISE).unwrap())
+    }
+
+    /// Start configuring a filter with a [`FilterBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let filter = Filter::builder().words(&["赌博"]).build()?;
+    /// assert!(filter.find_in("含有赌博").0);
+    /// # Ok::<(), sensitive_rs::BuildError>(())
+    /// ```
+    pub fn builder() -> FilterBuilder {
+        FilterBuilder::new()
+    }
+
+    /// An empty filter with the settings of `builder`, which [`FilterBuilder::build`] has
+    /// validated.
+    pub(crate) fn configured(builder: &FilterBuilder, noise: Regex) -> Self {
+        let mut engine = MultiPatternEngine::new(builder.algorithm, &[]);
+        engine.set_match_kind(builder.match_kind);
+        engine.set_overlapping(builder.overlapping);
+        #[cfg(feature = "std")]
+        let mut variant_detector = VariantDetector::new();
+        #[cfg(feature = "std")]
+        {
+            variant_detector.set_pinyin(builder.pinyin_variants);
+            variant_detector.set_shape(builder.shape_variants);
+        }
+        let compiled = Compiled {
+            engine,
+            #[cfg(feature = "std")]
+            variant_detector,
+            categories: HashMap::new(),
+            replacements: HashMap::new(),
+            severities: HashMap::new(),
+            layers: Layers::default(),
+        };
         Self {
-            engine: MultiPatternEngine::new(None, &[]),
+            compiled: Arc::new(compiled),
+            noise,
+            replace_strategy: ReplaceStrategy::default(),
             #[cfg(feature = "std")]
-            variant_detector: VariantDetector::new(),
-            noise: Regex::new(r"[^\w\s\u4e00-\u9fff]").unwrap(),
+            cache: Arc::new(Mutex::new(LruCache::new(
+                NonZero::new(builder.cache_capacity).expect("checked by FilterBuilder::build"),
+            ))),
+            #[cfg(feature = "parallel")]
+            parallel_threshold: builder.parallel_threshold,
+            fold_whitespace: builder.fold_whitespace,
+            disabled_layers: BTreeSet::new(),
+        }
+    }
+
+    /// A filter with an empty dictionary and the settings of this one: noise pattern,
+    /// whitespace folding, match kind, overlapping mode, variant channels and replacement
+    /// strategy. The algorithm is chosen afresh for the new dictionary's size.
+    pub(crate) fn empty_like(&self) -> Self {
+        let mut engine = MultiPatternEngine::new(None, &[]);
+        engine.set_match_kind(self.match_kind());
+        engine.set_overlapping(self.is_overlapping());
+        let compiled = Compiled {
+            engine,
             #[cfg(feature = "std")]
-            cache: Arc::new(Mutex::new(LruCache::new(NonZero::new(1000).unwrap()))), // Cache 1000 results
+            variant_detector: self.compiled.variant_detector.emptied(),
+            categories: HashMap::new(),
+            replacements: HashMap::new(),
+            severities: HashMap::new(),
+            layers: Layers::default(),
+        };
+        let mut filter = self.clone();
+        filter.compiled = Arc::new(compiled);
+        filter.disabled_layers.clear();
+        filter.reset_cache();
+        filter
+    }
+
+    /// Strip noise like [`Filter::remove_noise`], remembering where each kept run came from.
+    pub(crate) fn clean<'t>(&self, text: &'t str) -> CleanText<'t> {
+        let mut runs = Vec::new();
+        let mut out = String::new();
+        let mut last = 0usize;
+        for noise in self.noise.find_iter(text) {
+            if noise.start() > last {
+                runs.push((out.len(), last));
+                out.push_str(&text[last..noise.start()]);
+            }
+            last = noise.end();
+        }
+        if last == 0 {
+            return CleanText { text: Cow::Borrowed(text), offsets: OffsetMap { runs: Vec::new() } };
+        }
+        if last < text.len() {
+            runs.push((out.len(), last));
+            out.push_str(&text[last..]);
         }
+        CleanText { text: Cow::Owned(out), offsets: OffsetMap { runs } }
     }
 
+    /// The matching engine (for crate-internal scanners).
+    pub(crate) fn engine(&self) -> &MultiPatternEngine {
+        &self.compiled.engine
+    }
+
+    /// The variant detector (for crate-internal code generation).
+    #[cfg(feature = "codegen")]
+    pub(crate) fn variant_detector(&self) -> &VariantDetector {
+        &self.compiled.variant_detector
+    }
+
+    /// The dictionary, to be changed: copied first if clones share it.
+    fn compiled_mut(&mut self) -> &mut Compiled {
+        Arc::make_mut(&mut self.compiled)
+    }
+
+    /// Drop cached results after a change to the dictionary or noise pattern. A cache still
+    /// shared with clones is left to them and replaced by a new one.
+    fn reset_cache(&mut self) {
+        #[cfg(feature = "std")]
+        match Arc::get_mut(&mut self.cache) {
+            Some(cache) => cache.get_mut().unwrap_or_else(|e| e.into_inner()).clear(),
+            None => {
+                let capacity = self.cache.lock().unwrap_or_else(|e| e.into_inner()).cap();
+                self.cache = Arc::new(Mutex::new(LruCache::new(capacity)));
+            }
+        }
+    }
+
+    // Clones share the cache whatever their layers, so it only holds unmasked results.
     #[cfg(feature = "std")]
     fn check_cache(&self, text: &str) -> Option<Vec<String>> {
+        if self.is_masked() {
+            return None;
+        }
         self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(text).cloned()
     }
 
     #[cfg(feature = "std")]
     fn cache_result(&self, text: &str, results: &[String]) {
-        self.cache.lock().unwrap_or_else(|e| e.into_inner()).put(text.to_string(), results.to_vec());
+        if !self.is_masked() {
+            self.cache.lock().unwrap_or_else(|e| e.into_inner()).put(text.to_string(), results.to_vec());
+        }
     }
 
-    fn word_match_variants(word: &str) -> Vec<String> {
+    /// Whether any layer is disabled, so some dictionary words must not match.
+    fn is_masked(&self) -> bool {
+        !self.disabled_layers.is_empty()
+    }
+
+    /// Dictionary forms that currently match, for variant detection.
+    fn active_patterns(&self) -> Vec<&str> {
+        let patterns = self.compiled.engine.get_patterns().iter().map(String::as_str);
+        if self.is_masked() {
+            patterns.filter(|p| self.compiled.layers.is_active(p, &self.disabled_layers)).collect()
+        } else {
+            patterns.collect()
+        }
+    }
+
+    /// Exact matches over clean text among the words that currently match: every
+    /// occurrence with `overlapping`, else resolved by the match kind.
+    pub(crate) fn exact_matches(&self, text: &str, overlapping: bool) -> Vec<MatchInfo> {
+        self.exact_matches_except(text, overlapping, &HashSet::new())
+    }
+
+    /// [`Filter::exact_matches`] leaving out the match forms in `exempt`, so an exempt word
+    /// does not hide a shorter one inside it.
+    fn exact_matches_except(&self, text: &str, overlapping: bool, exempt: &HashSet<String>) -> Vec<MatchInfo> {
+        let engine = &self.compiled.engine;
+        if self.is_masked() || !exempt.is_empty() {
+            let keep = |p: &str| !exempt.contains(p) && self.compiled.layers.is_active(p, &self.disabled_layers);
+            return engine
+                .find_where(text, overlapping, keep)
+                .into_iter()
+                .map(|m| MatchInfo { pattern: m.pattern.to_string(), start: m.start, end: m.end })
+                .collect();
+        }
+        if overlapping { engine.find_overlapping(text) } else { engine.find_matches_with_positions(text) }
+    }
+
+    /// The layer to report for a match of `word`.
+    fn match_layer(&self, word: &str) -> Option<String> {
+        self.layer_of(word).map(String::from)
+    }
+
+    pub(crate) fn word_match_variants(&self, word: &str) -> Vec<String> {
         let mut variants = vec![word.to_string()];
-        if word.chars().any(char::is_whitespace) {
+        if self.fold_whitespace && word.chars().any(char::is_whitespace) {
             let folded: String = word.chars().filter(|c| !c.is_whitespace()).collect();
             if !folded.is_empty() && folded != word {
                 variants.push(folded);
@@ -100,15 +435,19 @@ impl Filter {
         variants
     }
 
-    fn extend_patterns_with_word_variants(patterns: &mut Vec<String>, words: &[&str]) {
-        let mut seen: HashSet<String> = patterns.iter().cloned().collect();
-        for word in words {
-            for variant in Self::word_match_variants(word) {
-                if seen.insert(variant.clone()) {
-                    patterns.push(variant);
-                }
-            }
-        }
+    /// Whether words containing whitespace also match with it removed.
+    pub(crate) fn folds_whitespace(&self) -> bool {
+        self.fold_whitespace
+    }
+
+    /// Match forms of `words` not yet in the dictionary, in order, without repeats.
+    fn new_patterns(&self, words: &[&str]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        words
+            .iter()
+            .flat_map(|word| self.word_match_variants(word))
+            .filter(|variant| !self.compiled.engine.contains(variant) && seen.insert(variant.clone()))
+            .collect()
     }
 
     /// Clear the cache
@@ -117,17 +456,132 @@ impl Filter {
         self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
     }
 
+    /// Report nested and overlapping dictionary entries (off by default).
+    ///
+    /// With overlapping mode on, [`Filter::find_all`] and [`Filter::find_occurrences`]
+    /// report every dictionary entry occurring in the text, so `赌博` is found inside
+    /// `赌博机` instead of being resolved away in favour of the longer word. The result
+    /// is the same on every [`MatchAlgorithm`]. Replacement, [`Filter::annotate`] and
+    /// [`Filter::find_iter`] still work on non-overlapping matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "赌博机"]);
+    /// let exact = |filter: &Filter| -> Vec<String> {
+    ///     filter.find_occurrences("赌博机").into_iter().filter(|m| !m.is_variant).map(|m| m.word).collect()
+    /// };
+    /// assert_eq!(exact(&filter), ["赌博机"]);
+    ///
+    /// filter.set_overlapping(true);
+    /// assert_eq!(exact(&filter), ["赌博机", "赌博"]);
+    /// ```
+    pub fn set_overlapping(&mut self, overlapping: bool) {
+        self.compiled_mut().engine.set_overlapping(overlapping);
+        self.reset_cache();
+    }
+
+    /// Whether overlapping mode is enabled (see [`Filter::set_overlapping`]).
+    pub fn is_overlapping(&self) -> bool {
+        self.compiled.engine.is_overlapping()
+    }
+
+    /// Set how overlapping dictionary words are resolved (see [`MatchKind`]).
+    ///
+    /// Applies to every method that reports or rewrites non-overlapping matches, with the
+    /// same result on every [`MatchAlgorithm`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, MatchKind};
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "赌博机"]);
+    /// assert_eq!(filter.replace("赌博机", '*'), "***");
+    ///
+    /// filter.set_match_kind(MatchKind::LeftmostFirst);
+    /// assert_eq!(filter.replace("赌博机", '*'), "**机");
+    /// ```
+    pub fn set_match_kind(&mut self, match_kind: MatchKind) {
+        self.compiled_mut().engine.set_match_kind(match_kind);
+        self.reset_cache();
+    }
+
+    /// The current [`MatchKind`].
+    #[must_use]
+    pub fn match_kind(&self) -> MatchKind {
+        self.compiled.engine.match_kind()
+    }
+
     /// Create with specific algorithm
+    ///
+    /// The algorithm is kept as words are added; it is not re-selected by dictionary size.
     pub fn with_algorithm(algorithm: MatchAlgorithm) -> Self {
-        Self { engine: MultiPatternEngine::new(Some(algorithm), &[]), ..Self::new() }
+        Self::configured(&FilterBuilder::new().algorithm(algorithm), Regex::new(DEFAULT_NOISE).unwrap())
+    }
+
+    /// Create a filter over a dictionary compiled at build time
+    ///
+    /// `dict` comes from a build script using `sensitive_rs::codegen` (feature `codegen`).
+    /// Nothing is parsed or compiled: the filter scans the generated automaton directly.
+    /// The words themselves are still copied to the heap, as by
+    /// [`MultiPatternEngine::from_static`], and with `std` so is the generated pinyin index:
+    /// allocation in proportion to the dictionary, but no automaton construction. Other
+    /// settings are the defaults, and can be changed as on any filter.
+    pub fn from_static(dict: &'static StaticDict) -> Self {
+        let mut filter = Self::new();
+        let compiled = filter.compiled_mut();
+        compiled.engine = MultiPatternEngine::from_static(dict);
+        #[cfg(feature = "std")]
+        compiled.variant_detector.add_static(dict);
+        filter
     }
 
     /// Load default dictionary
+    ///
+    /// With the `builtin-dict` feature this is the embedded [`Builtin::Default`] and never
+    /// fails. Without it, `dict/dict.txt` is read relative to the current directory.
     #[cfg(feature = "std")]
     pub fn with_default_dict() -> io::Result<Self> {
+        #[cfg(feature = "builtin-dict")]
+        return Ok(Self::with_builtin(Builtin::Default));
+        #[cfg(not(feature = "builtin-dict"))]
+        {
+            let mut filter = Self::new();
+            filter.load_word_dict("dict/dict.txt")?;
+            Ok(filter)
+        }
+    }
+
+    /// Create a filter with a dictionary compiled into the crate.
+    ///
+    /// No file is read, so this works from any directory and on `wasm`.
+    #[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+    pub fn with_builtin(builtin: Builtin) -> Self {
         let mut filter = Self::new();
-        filter.load_word_dict("dict/dict.txt")?;
-        Ok(filter)
+        filter.add_builtin(builtin);
+        filter
+    }
+
+    /// Add the words of a dictionary compiled into the crate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Builtin, Filter};
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("自定义词");
+    /// filter.add_builtin(Builtin::Default);
+    /// assert!(filter.find_in("自定义词").0 && filter.find_in("赌博").0);
+    /// ```
+    #[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+    pub fn add_builtin(&mut self, builtin: Builtin) {
+        self.add_words(&builtin.words().collect::<Vec<_>>());
     }
 
     /// Update noise pattern
@@ -149,10 +603,15 @@ impl Filter {
     /// ```
     pub fn update_noise_pattern(&mut self, pattern: &str) -> Result<(), regex::Error> {
         self.noise = Regex::new(pattern)?;
+        self.reset_cache();
         Ok(())
     }
 
     /// Add a sensitive word
+    ///
+    /// Adding and removing words one at a time is cheap: the change is matched alongside
+    /// the compiled dictionary and folded into it once enough changes accumulate (see
+    /// [`MultiPatternEngine::insert`]).
     pub fn add_word(&mut self, word: &str) {
         self.add_words(&[word]);
     }
@@ -169,23 +628,179 @@ impl Filter {
     /// assert!(filter.find_all("含有赌博和色情").contains(&"赌博".to_string()));
     /// ```
     pub fn add_words(&mut self, words: &[&str]) {
-        let mut patterns = self.engine.get_patterns().to_vec();
-        Self::extend_patterns_with_word_variants(&mut patterns, words);
+        self.mark_direct(words);
+        self.insert_words(words, true);
+    }
+
+    /// Record words added outside layers, so removing a layer keeps them.
+    fn mark_direct(&mut self, words: &[&str]) {
+        if self.compiled.layers.names().next().is_some() {
+            let forms: Vec<String> = words.iter().flat_map(|word| self.word_match_variants(word)).collect();
+            self.compiled_mut().layers.mark_direct(&forms);
+        }
+    }
 
-        self.engine.rebuild(&patterns);
+    /// Add words to the engine and, if `variants`, to the variant detector. Only forms new
+    /// to the dictionary are indexed, so re-adding a list costs no pinyin conversion.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn insert_words(&mut self, words: &[&str], variants: bool) {
+        let patterns = self.new_patterns(words);
+        let compiled = self.compiled_mut();
+        compiled.engine.insert(&patterns);
         #[cfg(feature = "std")]
+        if variants {
+            for pattern in &patterns {
+                compiled.variant_detector.add_word(pattern);
+            }
+        }
+        self.reset_cache();
+    }
+
+    /// Add dictionary entries with their metadata
+    ///
+    /// Each entry's category, priority, severity and replacement apply to the word and its
+    /// whitespace-folded form; an entry with `variants: false` is matched exactly only.
+    /// Re-adding a word replaces the metadata it sets. Notes are not kept.
+    #[cfg(feature = "std")]
+    pub fn add_entries(&mut self, entries: &[DictEntry]) {
+        self.mark_direct(&entries.iter().map(|e| e.word.as_str()).collect::<Vec<_>>());
+        for variants in [true, false] {
+            let words: Vec<&str> = entries.iter().filter(|e| e.variants == variants).map(|e| e.word.as_str()).collect();
+            self.insert_words(&words, variants);
+        }
+        for entry in entries {
+            for form in self.word_match_variants(&entry.word) {
+                let compiled = self.compiled_mut();
+                if let Some(category) = &entry.category {
+                    compiled.categories.insert(form.clone(), category.clone());
+                }
+                if let Some(replacement) = &entry.replacement {
+                    compiled.replacements.insert(form.clone(), replacement.clone());
+                }
+                if let Some(severity) = entry.severity {
+                    compiled.severities.insert(form.clone(), severity);
+                }
+                if let Some(priority) = entry.priority {
+                    compiled.engine.set_priority(&form, priority);
+                }
+            }
+        }
+        self.reset_cache();
+    }
+
+    /// Load a structured dictionary file, choosing the format by its extension (see
+    /// [`DictFormat::from_path`]), and add its entries with [`Filter::add_entries`].
+    ///
+    /// # Errors
+    ///
+    /// [`DictError`] if the file cannot be read or is malformed, with the line number of
+    /// the first bad entry. Nothing is added then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let path = std::env::temp_dir().join("sensitive-rs-doc-dict.csv");
+    /// std::fs::write(&path, "word,category,severity\n赌博,gambling,3\n")?;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.load_entries(&path)?;
+    /// assert_eq!(filter.severity_of("赌博"), Some(3));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn load_entries(&mut self, path: impl AsRef<Path>) -> Result<(), DictError> {
+        let entries = dict::read_entries(path.as_ref())?;
+        self.add_entries(&entries);
+        Ok(())
+    }
+
+    /// Add multiple words tagged with a category
+    ///
+    /// The category is used by [`ReplaceStrategy::Category`] and reported by
+    /// [`Filter::category_of`]. Re-adding a word with another category re-tags it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words_with_category(&["赌博", "博彩"], "gambling");
+    /// assert_eq!(filter.category_of("赌博"), Some("gambling"));
+    /// assert_eq!(filter.category_of("色情"), None);
+    /// ```
+    pub fn add_words_with_category(&mut self, words: &[&str], category: &str) {
+        self.tag_category(words, category);
+        self.add_words(words);
+    }
+
+    fn tag_category(&mut self, words: &[&str], category: &str) {
         for word in words {
-            for variant in Self::word_match_variants(word) {
-                self.variant_detector.add_word(&variant);
+            for variant in self.word_match_variants(word) {
+                self.compiled_mut().categories.insert(variant, category.to_string());
             }
         }
-        self.clear_cache();
+    }
+
+    /// Add multiple words with a priority for [`MatchKind::Priority`]
+    ///
+    /// Where matches overlap, the word with the higher priority wins; words added
+    /// without one have priority 0. Re-adding a word sets its new priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, MatchKind};
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.set_match_kind(MatchKind::Priority);
+    /// filter.add_words(&["赌博机"]);
+    /// filter.add_words_with_priority(&["博机"], 10);
+    /// assert_eq!(filter.priority_of("博机"), 10);
+    /// assert_eq!(filter.replace("赌博机", '*'), "赌**");
+    /// ```
+    pub fn add_words_with_priority(&mut self, words: &[&str], priority: i32) {
+        self.add_words(words);
+        for word in words {
+            for variant in self.word_match_variants(word) {
+                self.compiled_mut().engine.set_priority(&variant, priority);
+            }
+        }
+        self.reset_cache();
+    }
+
+    /// Get a word's priority (0 unless added with [`Filter::add_words_with_priority`])
+    #[must_use]
+    pub fn priority_of(&self, word: &str) -> i32 {
+        self.compiled.engine.priority(word)
+    }
+
+    /// Get the category a word was added with, if any
+    ///
+    /// Whitespace-folded forms of a word (see [`Filter::add_words`]) share its category.
+    #[must_use]
+    pub fn category_of(&self, word: &str) -> Option<&str> {
+        self.compiled.categories.get(word).map(String::as_str)
+    }
+
+    /// Get the severity a word was added with, if any (see [`Filter::add_entries`])
+    #[must_use]
+    pub fn severity_of(&self, word: &str) -> Option<u8> {
+        self.compiled.severities.get(word).copied()
+    }
+
+    /// Get the replacement text a word was added with, if any (see [`Filter::add_entries`])
+    #[must_use]
+    pub fn replacement_of(&self, word: &str) -> Option<&str> {
+        self.compiled.replacements.get(word).map(String::as_str)
     }
 
     /// Get the currently used algorithm
     #[must_use]
     pub fn current_algorithm(&self) -> MatchAlgorithm {
-        self.engine.current_algorithm()
+        self.compiled.engine.current_algorithm()
     }
 
     /// Remove a word
@@ -195,18 +810,210 @@ impl Filter {
 
     /// Remove multiple words
     pub fn del_words(&mut self, words: &[&str]) {
-        let word_set: HashSet<String> = words.iter().flat_map(|word| Self::word_match_variants(word)).collect();
-        let patterns: Vec<_> = self.engine.get_patterns().iter().filter(|w| !word_set.contains(*w)).cloned().collect();
+        let patterns: Vec<String> = words.iter().flat_map(|word| self.word_match_variants(word)).collect();
+        self.compiled_mut().layers.forget(&patterns);
+        self.remove_patterns(&patterns);
+    }
+
+    /// Remove match forms and their metadata from the dictionary.
+    fn remove_patterns(&mut self, patterns: &[String]) {
+        let compiled = self.compiled_mut();
+        for pattern in patterns {
+            compiled.categories.remove(pattern);
+            compiled.replacements.remove(pattern);
+            compiled.severities.remove(pattern);
+        }
+        compiled.engine.remove(patterns);
+        #[cfg(feature = "std")]
+        compiled.variant_detector.remove_words(patterns);
+        self.reset_cache();
+    }
+
+    /// Add `words` to the layer `name`, creating it if needed.
+    ///
+    /// A layer is a named group of words that can be removed as a unit
+    /// ([`Filter::remove_layer`]) and switched off and on ([`Filter::set_layer_enabled`],
+    /// [`Filter::with_layers`]) without recompiling the dictionary. Matches report the layer
+    /// they came from in [`Match::layer`]. A word in several layers matches while any of them
+    /// is enabled; a word also added with [`Filter::add_words`] always matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_layer("base", &["赌博"]);
+    /// filter.add_layer("event-2026-10", &["赌博机", "代开发票"]);
+    ///
+    /// assert_eq!(filter.find_first_match("赌博机").unwrap().layer.as_deref(), Some("event-2026-10"));
+    /// filter.set_layer_enabled("event-2026-10", false);
+    /// assert_eq!(filter.find_all("赌博机 代开发票"), ["赌博"]);
+    /// filter.remove_layer("event-2026-10");
+    /// assert_eq!(filter.layers().collect::<Vec<_>>(), ["base"]);
+    /// ```
+    pub fn add_layer(&mut self, name: &str, words: &[&str]) {
+        let forms: Vec<String> = words.iter().flat_map(|word| self.word_match_variants(word)).collect();
+        let compiled = self.compiled_mut();
+        compiled.layers.add(name, &forms, |form| compiled.engine.contains(form));
+        self.insert_words(words, true);
+    }
+
+    /// Remove the layer `name` and its words, except those also in another layer or added
+    /// with [`Filter::add_words`]. Returns `false` if there is no such layer.
+    pub fn remove_layer(&mut self, name: &str) -> bool {
+        let Some(orphaned) = self.compiled_mut().layers.remove(name) else { return false };
+        self.disabled_layers.remove(name);
+        self.remove_patterns(&orphaned);
+        true
+    }
+
+    /// Replace the words of the layer `name` with `words`, creating the layer if needed.
+    ///
+    /// Words only in the old layer are removed, so deletions in the source of a layer take
+    /// effect; the layer keeps its place and whether it is enabled. The change is a single
+    /// `&mut` call, so share the filter through a [`SharedFilter`](crate::SharedFilter) to
+    /// replace a layer while other threads keep matching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_layer("remote", &["赌博", "色情"]);
+    /// filter.replace_layer("remote", &["赌博", "代开发票"]);
+    ///
+    /// assert_eq!(filter.find_all("赌博 色情 代开发票"), ["代开发票", "赌博"]);
+    /// ```
+    pub fn replace_layer(&mut self, name: &str, words: &[&str]) {
+        let forms: Vec<String> = words.iter().flat_map(|word| self.word_match_variants(word)).collect();
+        let keep: HashSet<&str> = forms.iter().map(String::as_str).collect();
+        let compiled = self.compiled_mut();
+        let orphaned = compiled.layers.replace(name, &forms, |form| compiled.engine.contains(form));
+        let doomed: Vec<String> = orphaned.into_iter().filter(|form| !keep.contains(form.as_str())).collect();
+        if !doomed.is_empty() {
+            self.remove_patterns(&doomed);
+        }
+        self.insert_words(words, true);
+    }
+
+    /// Switch the layer `name` off or on for this filter. Returns `false` if there is no
+    /// such layer.
+    ///
+    /// Only this filter is affected: clones sharing its dictionary keep their own settings.
+    /// While any layer is off, matching resolves overlaps among the remaining words only
+    /// (a disabled longer word does not hide an enabled shorter one), takes a slower path
+    /// than the compiled automaton alone, and bypasses the result cache.
+    pub fn set_layer_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if self.compiled.layers.forms(name).is_none() {
+            return false;
+        }
+        if enabled {
+            self.disabled_layers.remove(name);
+        } else {
+            self.disabled_layers.insert(name.into());
+        }
+        true
+    }
+
+    /// Whether the layer `name` exists and is enabled.
+    #[must_use]
+    pub fn is_layer_enabled(&self, name: &str) -> bool {
+        self.compiled.layers.forms(name).is_some() && !self.disabled_layers.contains(name)
+    }
+
+    /// A clone of this filter with only the layers in `enabled` switched on, for a single
+    /// call or request. Cheap: the compiled dictionary is shared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_layer("base", &["赌博"]);
+    /// filter.add_layer("politics", &["色情"]);
+    ///
+    /// assert_eq!(filter.with_layers(&["politics"]).find_all("赌博色情"), ["色情"]);
+    /// assert_eq!(filter.find_all("赌博色情").len(), 2);
+    /// ```
+    #[must_use]
+    pub fn with_layers(&self, enabled: &[&str]) -> Self {
+        let mut filter = self.clone();
+        filter.disabled_layers = self.layers().filter(|name| !enabled.contains(name)).map(String::from).collect();
+        filter
+    }
 
-        self.engine.rebuild(&patterns);
-        self.clear_cache();
+    /// Layer names, in the order the layers were added.
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.compiled.layers.names()
+    }
+
+    /// The match forms in the layer `name`.
+    #[must_use]
+    pub fn layer_words(&self, name: &str) -> Option<&[String]> {
+        self.compiled.layers.forms(name)
     }
 
-    /// Load dictionary from file
+    /// The first enabled layer containing `word` (a match form, as in [`Match::word`]).
+    #[must_use]
+    pub fn layer_of(&self, word: &str) -> Option<&str> {
+        self.compiled.layers.layer_of(word, &self.disabled_layers)
+    }
+
+    /// Load word lists, one word per line. Words are trimmed and blank lines skipped.
+    ///
+    /// `path` is a file, a directory or a pattern such as `dict/*.txt`, where `*` and `?` in
+    /// the file name match any run of characters and any one character. A directory loads
+    /// the files directly inside it (hidden ones skipped) and a pattern the files it
+    /// matches, in name order, each into the [layer](Filter::add_layer) and category named
+    /// after its file stem: `dict/gambling.txt.gz` becomes `gambling`. Only `.txt` files,
+    /// possibly compressed, are loaded from a directory or pattern; structured dictionaries
+    /// go through [`Filter::load_entries`]. A single file is loaded as plain words whatever
+    /// its extension. Gzip (feature `dict-gzip`) and zstd (feature `dict-zstd`)
+    /// compressed files are decompressed, recognized by their content.
+    ///
+    /// # Errors
+    ///
+    /// If a file cannot be read or decompressed or has a line that is not UTF-8, with the
+    /// file and line in the message, or if a directory or pattern names no word lists.
+    /// Nothing is added then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let dir = std::env::temp_dir().join("sensitive-rs-doc-dicts");
+    /// std::fs::create_dir_all(&dir)?;
+    /// std::fs::write(dir.join("gambling.txt"), "赌博\n博彩\n")?;
+    /// std::fs::write(dir.join("fraud.txt"), "代开发票\n")?;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.load_word_dict(dir.join("*.txt"))?;
+    /// assert_eq!(filter.layers().collect::<Vec<_>>(), ["fraud", "gambling"]);
+    /// assert_eq!(filter.category_of("赌博"), Some("gambling"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
     #[cfg(feature = "std")]
     pub fn load_word_dict<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
-        let file = File::open(path)?;
-        self.load(BufReader::new(file))
+        let path = path.as_ref();
+        if path.is_file() {
+            let words = dict::read_words(path)?;
+            self.add_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+            return Ok(());
+        }
+        let lists = dict::dictionary_files(path)?
+            .iter()
+            .map(|file| Ok((dict::file_stem(file), dict::read_words(file)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        for (name, words) in &lists {
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            self.tag_category(&words, name);
+            self.add_layer(name, &words);
+        }
+        Ok(())
     }
 
     /// Load dictionary from reader
@@ -232,6 +1039,10 @@ impl Filter {
     }
 
     /// Load dictionary from URL
+    ///
+    /// Downloads the whole list on every call and only adds words. To keep a dictionary in
+    /// sync with a URL, with conditional requests and upstream deletions applied, use
+    /// [`RemoteDict`](crate::RemoteDict).
     #[cfg(feature = "net")]
     pub fn load_net_word_dict(&mut self, url: &str) -> io::Result<()> {
         // Build a client per call (lazy) rather than storing one, so a `Filter` can
@@ -246,14 +1057,15 @@ impl Filter {
             return Err(io::Error::other(format!("HTTP request failed: {}", response.status())));
         }
 
-        let reader = BufReader::new(response);
+        let reader = io::BufReader::new(response);
         self.load(reader)
     }
 
     /// Find the first sensitive word, returning a [`Match`] with details, or `None`.
     ///
     /// Exact matches are preferred; pinyin/shape variants are only consulted when no
-    /// exact hit is found. `is_variant` on the returned [`Match`] records which path hit.
+    /// exact hit is found, and then the variant starting earliest in the text is returned.
+    /// `is_variant` on the returned [`Match`] records which path hit.
     ///
     /// # Examples
     ///
@@ -266,41 +1078,241 @@ impl Filter {
     /// // Exact hit:
     /// assert_eq!(
     ///     filter.find_first_match("含有赌博"),
-    ///     Some(Match { word: "赌博".to_string(), is_variant: false })
+    ///     Some(Match::new("赌博", false, 6, 12))
     /// );
     /// // Pinyin variant (no exact hit):
     /// assert_eq!(
     ///     filter.find_first_match("dubo"),
-    ///     Some(Match { word: "赌博".to_string(), is_variant: true })
+    ///     Some(Match::new("赌博", true, 0, 4))
     /// );
     /// // No match:
     /// assert_eq!(filter.find_first_match("clean text"), None);
     /// ```
     #[must_use]
     pub fn find_first_match(&self, text: &str) -> Option<Match> {
-        let clean_text = self.remove_noise(text);
+        let clean = self.clean(text);
+        let clean_text = clean.text.as_ref();
 
-        // 1. Try exact match first
-        if let Some(word) = self.engine.find_first(&clean_text) {
-            return Some(Match { word, is_variant: false });
+        // 1. Try exact match first. The first hit is also the word's first occurrence:
+        //    an earlier occurrence would have been found first.
+        let first = if self.is_masked() {
+            self.exact_matches(clean_text, false).into_iter().next().map(|m| (m.pattern, m.start))
+        } else {
+            self.compiled.engine.find_iter(clean_text).next().map(|m| (m.pattern.to_string(), m.start))
+        };
+        if let Some((word, start)) = first {
+            let (start, end) = clean.input_span(start, start + word.len());
+            let layer = self.match_layer(&word);
+            return Some(Match { word, is_variant: false, start, end, layer });
         }
 
         // 2. Try variant detection (requires `std`: pinyin/shape detection)
         #[cfg(feature = "std")]
         {
-            let patterns: Vec<_> = self.engine.get_patterns().iter().map(|s| s.as_str()).collect();
-            if let Some(word) = self.variant_detector.detect(&clean_text, &patterns).first() {
-                return Some(Match { word: word.to_string(), is_variant: true });
+            let patterns = self.active_patterns();
+            let spans = self.compiled.variant_detector.detect_with_spans(clean_text, &patterns);
+            if let Some((span, word)) = spans.into_iter().min_by_key(|(span, _)| span.start) {
+                let (start, end) = clean.input_span(span.start, span.end);
+                return Some(Match {
+                    word: word.to_string(),
+                    is_variant: true,
+                    start,
+                    end,
+                    layer: self.match_layer(word),
+                });
             }
         }
 
         None
     }
 
-    /// Find first sensitive word.
+    /// Find first sensitive word.
+    ///
+    /// Returns `(found, word)`; `word` is empty when nothing matched. For richer detail
+    /// (including whether the hit was a variant), use [`Filter::find_first_match`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    /// assert_eq!(filter.find_in("含有赌博"), (true, "赌博".to_string()));
+    /// assert_eq!(filter.find_in("clean text"), (false, String::new()));
+    /// ```
+    #[must_use]
+    pub fn find_in(&self, text: &str) -> (bool, String) {
+        match self.find_first_match(text) {
+            Some(m) => (true, m.word),
+            None => (false, String::new()),
+        }
+    }
+
+    /// Replace sensitive words with replacement character.
+    ///
+    /// Each matched character is replaced by one `replacement` char; this is
+    /// [`Filter::replace_with_strategy`] with [`ReplaceStrategy::Mask`]. Only exact
+    /// dictionary matches are masked; variant forms are not (use [`Filter::find_all`]
+    /// to detect them).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    /// assert_eq!(filter.replace("含有赌博内容", '*'), "含有**内容");
+    /// ```
+    #[must_use]
+    pub fn replace(&self, text: &str, replacement: char) -> String {
+        self.replace_with_strategy(text, &ReplaceStrategy::Mask(replacement))
+    }
+
+    /// Replace sensitive words according to a [`ReplaceStrategy`].
+    ///
+    /// Matches are resolved by the filter's [`MatchKind`] and rewritten in a single pass,
+    /// so the output is the same on every [`MatchAlgorithm`]. [`ReplaceStrategy::Category`]
+    /// looks categories up with [`Filter::category_of`]. Like [`Filter::replace`], only
+    /// exact dictionary matches are rewritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, ReplaceStrategy};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words_with_category(&["赌博"], "gambling");
+    /// filter.add_word("色情");
+    ///
+    /// let strategy = ReplaceStrategy::Category {
+    ///     replacements: BTreeMap::from([("gambling".to_string(), "[赌]".to_string())]),
+    ///     fallback: Box::new(ReplaceStrategy::Mask('*')),
+    /// };
+    /// assert_eq!(filter.replace_with_strategy("赌博和色情", &strategy), "[赌]和**");
+    /// ```
+    #[must_use]
+    pub fn replace_with_strategy(&self, text: &str, strategy: &ReplaceStrategy) -> String {
+        self.replace_with(text, |m| strategy.replacement(&m.word, self.category_of(&m.word)))
+    }
+
+    /// Replace sensitive words with this filter's own [`ReplaceStrategy`]
+    /// ([`ReplaceStrategy::Mask('*')`](ReplaceStrategy::Mask) unless changed by
+    /// [`Filter::set_replace_strategy`]). Words added with their own replacement text (see
+    /// [`Filter::add_entries`]) are replaced by that instead.
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        self.replace_with(text, |m| match self.replacement_of(&m.word) {
+            Some(replacement) => Cow::Borrowed(replacement),
+            None => self.replace_strategy.replacement(&m.word, self.category_of(&m.word)),
+        })
+    }
+
+    /// Set the strategy used by [`Filter::redact`]. Only this filter is affected, not its clones.
+    pub fn set_replace_strategy(&mut self, strategy: ReplaceStrategy) {
+        self.replace_strategy = strategy;
+    }
+
+    /// The strategy used by [`Filter::redact`].
+    #[must_use]
+    pub fn replace_strategy(&self) -> &ReplaceStrategy {
+        &self.replace_strategy
+    }
+
+    /// Replace sensitive words with text chosen per match by `replacement`.
+    ///
+    /// Matches are resolved by the filter's [`MatchKind`], exactly as in
+    /// [`Filter::replace`]; the closure sees each one as a [`Match`] whose `start..end`
+    /// indexes `text`, so it can return `&text[m.start..m.end]` to leave a word alone. Like [`Filter::replace`],
+    /// the result has noise stripped and only exact dictionary matches are rewritten;
+    /// use [`Filter::replace_with_variants`] to also rewrite pinyin/shape variants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "色情"]);
+    ///
+    /// let text = "赌博和色情";
+    /// let out = filter.replace_with(text, |m| match m.word.as_str() {
+    ///     "赌博" => Cow::Owned(format!("[{}]({})", m.word, "/policy#gambling")),
+    ///     _ => Cow::Borrowed(&text[m.start..m.end]),
+    /// });
+    /// assert_eq!(out, "[赌博](/policy#gambling)和色情");
+    /// ```
+    #[must_use]
+    pub fn replace_with<'r>(&self, text: &str, replacement: impl FnMut(&Match) -> Cow<'r, str>) -> String {
+        self.replace_resolved(text, false, replacement)
+    }
+
+    /// Like [`Filter::replace_with`], but pinyin/shape variant matches are rewritten too.
+    ///
+    /// Exact matches are resolved by the filter's [`MatchKind`] first; variant matches then
+    /// fill the gaps between them, leftmost-longest, and never displace an exact one. Without the `std`
+    /// feature there is no variant detection and this is [`Filter::replace_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    ///
+    /// let out = filter.replace_with_variants("赌博 dubo", |m| {
+    ///     Cow::Borrowed(if m.is_variant { "[variant]" } else { "[exact]" })
+    /// });
+    /// assert_eq!(out, "[exact] [variant]");
+    /// ```
+    #[must_use]
+    pub fn replace_with_variants<'r>(&self, text: &str, replacement: impl FnMut(&Match) -> Cow<'r, str>) -> String {
+        self.replace_resolved(text, true, replacement)
+    }
+
+    /// Annotate the original text, wrapping every match in `markup`.
+    ///
+    /// Unlike the replace methods, the input is returned in full (noise included), so a
+    /// review tool can show exactly what was submitted. Matches come from the same
+    /// resolution as [`Filter::replace_with_variants`], by the filter's [`MatchKind`]: exact
+    /// and pinyin/shape variant matches are both marked, distinguished by their kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, Markup};
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    ///
+    /// assert_eq!(
+    ///     filter.annotate("赌!博 & dubo", &Markup::Html),
+    ///     concat!(
+    ///         r#"<mark data-word="赌博" data-kind="exact">赌!博</mark> &amp; "#,
+    ///         r#"<mark data-word="赌博" data-kind="variant">dubo</mark>"#,
+    ///     )
+    /// );
+    /// ```
+    #[must_use]
+    pub fn annotate(&self, text: &str, markup: &Markup) -> String {
+        let clean = self.clean(text);
+        let resolved = self.resolve_matches(&clean, true);
+        markup.render(text, resolved.iter().map(|(_, m)| m))
+    }
+
+    /// Every occurrence of a dictionary word, in text order.
     ///
-    /// Returns `(found, word)`; `word` is empty when nothing matched. For richer detail
-    /// (including whether the hit was a variant), use [`Filter::find_first_match`].
+    /// Unlike [`Filter::find_all`], repeats are kept and each [`Match`] carries its byte
+    /// span in `text`. Exact and pinyin/shape variant hits are both reported, resolved
+    /// without overlap by the filter's [`MatchKind`] exactly as [`Filter::annotate`] marks
+    /// them, with variants only between exact matches. In
+    /// overlapping mode (see [`Filter::set_overlapping`]) nothing is resolved away: every
+    /// exact and variant hit is returned, sorted by start.
     ///
     /// # Examples
     ///
@@ -309,22 +1321,31 @@ impl Filter {
     ///
     /// let mut filter = Filter::new();
     /// filter.add_word("赌博");
-    /// assert_eq!(filter.find_in("含有赌博"), (true, "赌博".to_string()));
-    /// assert_eq!(filter.find_in("clean text"), (false, String::new()));
+    ///
+    /// let found = filter.find_occurrences("赌博, 赌博, dubo");
+    /// assert_eq!(found.len(), 3);
+    /// assert_eq!((found[1].start, found[1].end), (8, 14));
+    /// assert!(found[2].is_variant);
     /// ```
     #[must_use]
-    pub fn find_in(&self, text: &str) -> (bool, String) {
-        match self.find_first_match(text) {
-            Some(m) => (true, m.word),
-            None => (false, String::new()),
+    pub fn find_occurrences(&self, text: &str) -> Vec<Match> {
+        let clean = self.clean(text);
+        if !self.compiled.engine.is_overlapping() {
+            return self.resolve_matches(&clean, true).into_iter().map(|(_, m)| m).collect();
         }
+        self.match_candidates(&clean.text, true, true, &HashSet::new())
+            .into_iter()
+            .map(|(span, is_variant, word)| {
+                let (start, end) = clean.input_span(span.start, span.end);
+                let layer = self.match_layer(&word);
+                Match { word, is_variant, start, end, layer }
+            })
+            .collect()
     }
 
-    /// Replace sensitive words with replacement character.
+    /// Number of occurrences of each dictionary word (exact and variant hits combined).
     ///
-    /// Each matched character is replaced by one `replacement` char. Only exact
-    /// dictionary matches are masked; variant forms are not (use [`Filter::find_all`]
-    /// to detect them).
+    /// Aggregates [`Filter::find_occurrences`]; words that do not occur are absent.
     ///
     /// # Examples
     ///
@@ -332,26 +1353,82 @@ impl Filter {
     /// use sensitive_rs::Filter;
     ///
     /// let mut filter = Filter::new();
-    /// filter.add_word("赌博");
-    /// assert_eq!(filter.replace("含有赌博内容", '*'), "含有**内容");
+    /// filter.add_words(&["赌博", "色情"]);
+    ///
+    /// let counts = filter.word_counts("赌博赌博色情dubo");
+    /// assert_eq!(counts["赌博"], 3);
+    /// assert_eq!(counts["色情"], 1);
     /// ```
     #[must_use]
-    pub fn replace(&self, text: &str, replacement: char) -> String {
-        let clean_text = self.remove_noise(text);
-        let repl = replacement.to_string();
-
-        // Single pass over leftmost-longest non-overlapping matches: one replacement
-        // char per matched character. Exact matches only — see the doc note above.
-        let matches = self.leftmost_longest_matches(&clean_text);
-        let mut result = String::with_capacity(clean_text.len());
-        let mut cursor = 0usize;
-        for m in &matches {
-            result.push_str(&clean_text[cursor..m.start]);
-            result.push_str(&repl.repeat(clean_text[m.start..m.end].chars().count()));
-            cursor = m.end;
+    pub fn word_counts(&self, text: &str) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for m in self.find_occurrences(text) {
+            *counts.entry(m.word).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn replace_resolved<'r>(
+        &self,
+        text: &str,
+        include_variants: bool,
+        mut replacement: impl FnMut(&Match) -> Cow<'r, str>,
+    ) -> String {
+        let clean = self.clean(text);
+        let resolved = self.resolve_matches(&clean, include_variants);
+        replace::rebuild(&clean.text, resolved.iter().map(|(span, m)| (span.clone(), replacement(m))))
+    }
+
+    /// Non-overlapping matches over the clean text, each paired with its clean-text span.
+    /// Exact matches are resolved by the engine's [`MatchKind`](crate::MatchKind); with
+    /// `include_variants`, variant spans then fill the gaps between them (see [`fill_gaps`]).
+    fn resolve_matches(&self, clean: &CleanText<'_>, include_variants: bool) -> Vec<(core::ops::Range<usize>, Match)> {
+        let candidates = self.match_candidates(&clean.text, include_variants, false, &HashSet::new());
+        let (exact, variants) = candidates.into_iter().partition(|(_, is_variant, _)| !is_variant);
+        fill_gaps(exact, variants, |(span, _, _)| span)
+            .into_iter()
+            .map(|(span, is_variant, word)| {
+                let (start, end) = clean.input_span(span.start, span.end);
+                let layer = self.match_layer(&word);
+                (span, Match { word, is_variant, start, end, layer })
+            })
+            .collect()
+    }
+
+    /// Candidate matches over the clean text, sorted by start ascending, then longest
+    /// first, then exact before variant. Exact candidates are every occurrence with
+    /// `overlapping` and the engine's resolved matches otherwise. Match forms in `exempt`
+    /// are left out, and so are variant spans whose text is itself a dictionary form.
+    pub(crate) fn match_candidates(
+        &self,
+        clean_text: &str,
+        include_variants: bool,
+        overlapping: bool,
+        exempt: &HashSet<String>,
+    ) -> Vec<Candidate> {
+        let exact = self.exact_matches_except(clean_text, overlapping, exempt);
+        let mut candidates: Vec<Candidate> = exact.into_iter().map(|m| (m.start..m.end, false, m.pattern)).collect();
+
+        #[cfg(feature = "std")]
+        if include_variants {
+            let mut patterns = self.active_patterns();
+            if !exempt.is_empty() {
+                patterns.retain(|p| !exempt.contains(*p));
+            }
+            candidates.extend(
+                self.compiled
+                    .variant_detector
+                    .detect_with_spans(clean_text, &patterns)
+                    .into_iter()
+                    .filter(|(span, _)| !self.compiled.engine.contains(&clean_text[span.clone()]))
+                    .map(|(span, word)| (span, true, word.to_string())),
+            );
         }
-        result.push_str(&clean_text[cursor..]);
-        result
+        #[cfg(not(feature = "std"))]
+        let _ = include_variants;
+
+        candidates.sort_by(|a, b| a.0.start.cmp(&b.0.start).then(b.0.end.cmp(&a.0.end)).then(a.1.cmp(&b.1)));
+        candidates
     }
 
     /// Filter out sensitive words (remove them completely).
@@ -371,7 +1448,11 @@ impl Filter {
     #[must_use]
     pub fn filter(&self, text: &str) -> String {
         let clean_text = self.remove_noise(text);
-        self.engine.replace_all(&clean_text, "")
+        if self.is_masked() {
+            let matches = self.exact_matches(&clean_text, false);
+            return replace::rebuild(&clean_text, matches.iter().map(|m| (m.start..m.end, Cow::Borrowed(""))));
+        }
+        self.compiled.engine.replace_all(&clean_text, "")
     }
 
     /// Validate text
@@ -394,6 +1475,25 @@ impl Filter {
         self.find_in(text)
     }
 
+    /// Check a word list against this filter's noise pattern and whitespace folding.
+    ///
+    /// The [`DictionaryReport`] lists entries that can never match, entries containing a
+    /// shorter entry, entries that are duplicates after normalization, and overly short
+    /// entries. Blank entries are skipped. The filter's own dictionary is not consulted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let report = Filter::new().lint(&["赌博", "赌.博"]);
+    /// assert_eq!(report.issues[0].to_string(), "line 2: 赌.博: unreachable: noise removal leaves `赌博`");
+    /// ```
+    #[must_use]
+    pub fn lint(&self, words: &[&str]) -> DictionaryReport {
+        DictionaryReport::new(self, words)
+    }
+
     /// Remove only specific noise characters, preserve spaces
     #[must_use]
     pub fn remove_noise(&self, text: &str) -> String {
@@ -406,23 +1506,39 @@ impl Filter {
         &self.noise
     }
 
-    /// Greedy leftmost-longest non-overlapping exact matches (byte spans + pattern).
+    /// Lazily iterate over exact matches without allocating a `String` per hit.
     ///
-    /// Sorts by start ascending then end descending (longest first at each start) and
-    /// keeps a match only when it begins at or after the previous kept match's end.
-    /// Shared by [`Filter::replace`] and [`Filter::find_all_layered`].
-    fn leftmost_longest_matches(&self, clean_text: &str) -> Vec<MatchInfo> {
-        let mut matches = self.engine.find_matches_with_positions(clean_text);
-        matches.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
-        let mut kept = Vec::with_capacity(matches.len());
-        let mut cursor = 0usize;
-        for m in matches {
-            if m.start >= cursor {
-                cursor = m.end;
-                kept.push(m);
-            }
-        }
-        kept
+    /// Yields non-overlapping [`MatchRef`]s in text order, resolved by the filter's
+    /// [`MatchKind`]: the pattern is borrowed from the dictionary, `pattern_id` indexes
+    /// [`MultiPatternEngine::get_patterns`], and `start..end` is the byte span in `text`
+    /// (covering any noise stripped from inside the word). Matches are located as the
+    /// iterator advances, so stopping early skips the rest of the scan. Variants are not
+    /// reported and the result cache is bypassed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "色情"]);
+    ///
+    /// let text = "赌博、色情、赌博、赌博";
+    /// assert!(filter.find_iter(text).nth(3).is_some());
+    /// let first = filter.find_iter(text).next().unwrap();
+    /// assert_eq!((first.pattern, first.start, first.end), ("赌博", 0, 6));
+    /// ```
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = MatchRef<'a>> + 'a {
+        let CleanText { text, offsets } = self.clean(text);
+        let engine = &self.compiled.engine;
+        let masked = self
+            .is_masked()
+            .then(|| engine.find_where(&text, false, |p| self.compiled.layers.is_active(p, &self.disabled_layers)));
+        let lazy = masked.is_none().then(|| engine.find_iter(text));
+        masked.into_iter().flatten().chain(lazy.into_iter().flatten()).map(move |m| {
+            let (start, end) = offsets.input_span(m.start, m.end);
+            MatchRef { start, end, ..m }
+        })
     }
 
     /// Optimized method of finding all sensitive words.
@@ -451,7 +1567,7 @@ impl Filter {
         }
 
         #[cfg(feature = "parallel")]
-        let results = if clean_text.len() > 1000 {
+        let results = if clean_text.len() > self.parallel_threshold {
             self.find_all_parallel(&clean_text) // long text -> parallel
         } else {
             self.find_all_sequential(&clean_text) // short text -> sequential
@@ -473,11 +1589,11 @@ impl Filter {
     /// whitespace-split parallelization dropped cross-segment variants).
     #[cfg(feature = "parallel")]
     fn find_all_parallel(&self, text: &str) -> Vec<String> {
-        let patterns: Vec<&str> = self.engine.get_patterns().iter().map(|s| s.as_str()).collect();
+        let patterns = self.active_patterns();
 
         let (engine_results, variant_results) = rayon::join(
-            || self.engine.find_all(text),
-            || self.variant_detector.detect(text, &patterns).into_iter().map(String::from).collect::<Vec<_>>(),
+            || self.exact_words(text),
+            || self.compiled.variant_detector.detect(text, &patterns).into_iter().map(String::from).collect::<Vec<_>>(),
         );
 
         let mut results = engine_results;
@@ -487,18 +1603,41 @@ impl Filter {
 
     /// Sequential processing version - suitable for short text
     fn find_all_sequential(&self, text: &str) -> Vec<String> {
-        let mut results = self.engine.find_all(text);
+        let mut results = self.exact_words(text);
 
         // Add variant detection results (std only: pinyin/shape detection)
         #[cfg(feature = "std")]
         {
-            let patterns: Vec<_> = self.engine.get_patterns().iter().map(|s| s.as_str()).collect();
-            results.extend(self.variant_detector.detect(text, &patterns).into_iter().map(|s| s.to_string()));
+            let patterns = self.active_patterns();
+            results.extend(self.compiled.variant_detector.detect(text, &patterns).into_iter().map(|s| s.to_string()));
         }
 
         self.deduplicate_and_sort(results)
     }
 
+    /// Exact matches of the clean `text`, as words: every occurrence in overlapping mode.
+    fn exact_words(&self, text: &str) -> Vec<String> {
+        if self.is_masked() {
+            return self.exact_matches(text, self.is_overlapping()).into_iter().map(|m| m.pattern).collect();
+        }
+        self.compiled.engine.find_all(text)
+    }
+
+    /// The words [`Filter::find_all`] reports for the clean `text`, unsorted and uncached,
+    /// leaving out the match forms in `exempt`.
+    pub(crate) fn words_present(&self, text: &str, exempt: &HashSet<String>) -> Vec<String> {
+        let exact = self.exact_matches_except(text, self.is_overlapping(), exempt);
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut words: Vec<String> = exact.into_iter().map(|m| m.pattern).collect();
+        #[cfg(feature = "std")]
+        {
+            let mut patterns = self.active_patterns();
+            patterns.retain(|p| !exempt.contains(*p));
+            words.extend(self.compiled.variant_detector.detect(text, &patterns).into_iter().map(String::from));
+        }
+        words
+    }
+
     /// Deduplication and sort
     fn deduplicate_and_sort(&self, mut results: Vec<String>) -> Vec<String> {
         results.sort_unstable();
@@ -554,7 +1693,7 @@ impl Filter {
     #[must_use]
     pub fn find_all_layered(&self, text: &str) -> Vec<String> {
         let clean_text = self.remove_noise(text);
-        let matches = self.leftmost_longest_matches(&clean_text);
+        let matches = self.exact_matches(&clean_text, false);
 
         // The longest exact matches...
         let mut results: Vec<String> = matches.iter().map(|m| m.pattern.clone()).collect();
@@ -572,8 +1711,8 @@ impl Filter {
             }
             remaining.push_str(&clean_text[cursor..]);
 
-            let patterns: Vec<&str> = self.engine.get_patterns().iter().map(|s| s.as_str()).collect();
-            results.extend(self.variant_detector.detect(&remaining, &patterns).into_iter().map(String::from));
+            let patterns = self.active_patterns();
+            results.extend(self.compiled.variant_detector.detect(&remaining, &patterns).into_iter().map(String::from));
         }
 
         self.deduplicate_and_sort(results)
@@ -582,7 +1721,9 @@ impl Filter {
     /// Streaming version - suitable for oversized text
     ///
     /// Reads line-by-line from any [`BufRead`] and returns the de-duplicated matches
-    /// across all lines. Handy for files too large to hold in memory.
+    /// across all lines. Handy for files too large to hold in memory. Words split by a
+    /// line break are not found; use [`Filter::scan_reader`] for positioned matches that
+    /// are found wherever the input is split.
     ///
     /// # Examples
     ///
@@ -609,6 +1750,177 @@ impl Filter {
 
         Ok(self.deduplicate_and_sort(all_results))
     }
+
+    /// Scan a reader of any size, yielding every exact match with its position
+    ///
+    /// The input is read in chunks (see [`StreamScanner::with_chunk_size`]) and the tail of
+    /// each chunk that could start an unfinished word is carried into the next, so matches
+    /// are the same as [`Filter::find_occurrences`] would report for the whole text (exact
+    /// hits only), in bounded memory. Noise is stripped as usual; each [`StreamMatch`](crate::StreamMatch)
+    /// carries absolute byte offsets into the input plus its line and column.
+    ///
+    /// With [`MatchKind::Priority`], a word is resolved without seeing further than the
+    /// longest dictionary word past its start, so an overlapping higher-priority word may
+    /// occasionally lose to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "色情"]);
+    ///
+    /// let input = "第一行\n含有赌博和色情";
+    /// let found: Vec<_> = filter.scan_reader(input.as_bytes()).with_chunk_size(4).collect::<Result<_, _>>()?;
+    /// assert_eq!(found.len(), 2);
+    /// assert_eq!((found[0].word.as_str(), found[0].start, found[0].line, found[0].column), ("赌博", 16, 2, 3));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn scan_reader<R: Read>(&self, reader: R) -> StreamScanner<'_, R> {
+        StreamScanner::new(self, reader)
+    }
+
+    /// Create a [`Scanner`] for text that arrives in fragments, such as chat messages typed
+    /// live or streamed model output.
+    ///
+    /// Words split across fragments are found, and only the minimal unfinished tail is kept
+    /// between pushes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    ///
+    /// let mut scanner = filter.scanner();
+    /// assert!(scanner.push("一起赌").is_empty());
+    /// let found = scanner.push("博吧");
+    /// assert_eq!((found[0].word.as_str(), found[0].start, found[0].end), ("赌博", 6, 12));
+    /// assert!(scanner.finish().is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn scanner(&self) -> Scanner<'_> {
+        Scanner::new(self)
+    }
+
+    /// Write a snapshot of the built filter for [`Filter::load_snapshot`].
+    ///
+    /// The snapshot starts with the magic bytes `SRSNAP` and the format version
+    /// ([`SNAPSHOT_VERSION`](crate::SNAPSHOT_VERSION)) and ends with a checksum of its contents.
+    /// The snapshot holds the expanded dictionary, categories and priorities, the pinyin
+    /// index, the Wu-Manber tables and every setting including the noise pattern and
+    /// [`Filter::redact`]'s strategy. The result cache is not stored.
+    ///
+    /// # Errors
+    ///
+    /// Any error of `writer`, or [`io::ErrorKind::InvalidInput`] if the strategy nests more
+    /// than 32 [`ReplaceStrategy::Category`] fallbacks, which a snapshot cannot hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_words(&["赌博", "色情"]);
+    ///
+    /// let mut bytes = Vec::new();
+    /// filter.save_snapshot(&mut bytes)?;
+    /// let loaded = Filter::load_snapshot(bytes.as_slice())?;
+    /// assert_eq!(loaded.find_all("dubo 色@情").len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn save_snapshot(&self, writer: impl io::Write) -> io::Result<()> {
+        if snapshot::strategy_depth(&self.replace_strategy) > snapshot::MAX_STRATEGY_DEPTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "replacement strategy nested too deeply"));
+        }
+        let mut e = Encoder::default();
+        e.str(self.noise.as_str());
+        e.bool(self.fold_whitespace);
+        #[cfg(feature = "parallel")]
+        e.usize(self.parallel_threshold);
+        #[cfg(not(feature = "parallel"))]
+        e.usize(DEFAULT_PARALLEL_THRESHOLD);
+        e.usize(self.cache.lock().unwrap_or_else(|e| e.into_inner()).cap().get());
+        snapshot::encode_strategy(&mut e, &self.replace_strategy);
+        for map in [&self.compiled.categories, &self.compiled.replacements] {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable();
+            e.usize(entries.len());
+            for (word, value) in entries {
+                e.str(word);
+                e.str(value);
+            }
+        }
+        let mut severities: Vec<_> = self.compiled.severities.iter().collect();
+        severities.sort_unstable();
+        e.usize(severities.len());
+        for (word, &severity) in severities {
+            e.str(word);
+            e.u8(severity);
+        }
+        self.compiled.layers.encode(&mut e);
+        e.strs(&self.disabled_layers.iter().collect::<Vec<_>>());
+        self.compiled.engine.encode(&mut e);
+        self.compiled.variant_detector.encode(&mut e);
+        snapshot::write(writer, &e.into_bytes())
+    }
+
+    /// Load a filter written by [`Filter::save_snapshot`].
+    ///
+    /// The header, format version and checksum are verified before anything is decoded, and
+    /// every field is range-checked, so a damaged or foreign file is an error rather than a
+    /// misbehaving filter. Only the Aho-Corasick or regex automaton is compiled, from the
+    /// stored patterns; Wu-Manber tables are reused unless they were hashed by an
+    /// incompatible build, in which case they are rebuilt.
+    ///
+    /// # Errors
+    ///
+    /// [`SnapshotError`] if the input cannot be read or is not a valid snapshot of this
+    /// format version.
+    #[cfg(feature = "std")]
+    pub fn load_snapshot(reader: impl Read) -> Result<Self, SnapshotError> {
+        let payload = snapshot::read(reader)?;
+        let mut d = Decoder::new(&payload);
+        let noise = Regex::new(d.str()?).map_err(SnapshotError::NoisePattern)?;
+        let fold_whitespace = d.bool()?;
+        #[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+        let parallel_threshold = d.usize()?;
+        let cache_capacity = NonZero::new(d.usize()?).ok_or(SnapshotError::Corrupt("zero cache capacity"))?;
+        let replace_strategy = snapshot::decode_strategy(&mut d)?;
+        let mut maps = [HashMap::new(), HashMap::new()];
+        for map in &mut maps {
+            for _ in 0..d.len()? {
+                map.insert(d.string()?, d.string()?);
+            }
+        }
+        let [categories, replacements] = maps;
+        let mut severities = HashMap::new();
+        for _ in 0..d.len()? {
+            severities.insert(d.string()?, d.u8()?);
+        }
+        let layers = Layers::decode(&mut d)?;
+        let disabled_layers = d.strings()?.into_iter().collect();
+        let engine = MultiPatternEngine::decode(&mut d)?;
+        let variant_detector = VariantDetector::decode(&mut d)?;
+        d.finish()?;
+
+        Ok(Self {
+            compiled: Arc::new(Compiled { engine, variant_detector, categories, replacements, severities, layers }),
+            disabled_layers,
+            noise,
+            replace_strategy,
+            cache: Arc::new(Mutex::new(LruCache::new(cache_capacity))),
+            #[cfg(feature = "parallel")]
+            parallel_threshold,
+            fold_whitespace,
+        })
+    }
 }
 
 /// Async dictionary loading (non-blocking I/O). Enable with the `async-io` feature
@@ -640,6 +1952,51 @@ impl Filter {
         Ok(())
     }
 
+    /// Scan an [`AsyncRead`](tokio::io::AsyncRead) without buffering it, as an async stream
+    /// of positioned matches.
+    ///
+    /// The async counterpart of [`Filter::scan_reader`], with the same results. Matching
+    /// runs on tokio's blocking pool, so a large body does not stall the executor. Must be
+    /// called from within a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main] async fn main() -> std::io::Result<()> {
+    /// use futures_core::Stream;
+    /// use sensitive_rs::Filter;
+    /// use std::sync::Arc;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    /// let filter = Arc::new(filter);
+    ///
+    /// let mut matches = filter.scan_async_read(std::io::Cursor::new("请求体里有赌博"));
+    /// let next = std::future::poll_fn(|cx| std::pin::Pin::new(&mut matches).poll_next(cx)).await;
+    /// assert_eq!(next.unwrap()?.start, 15);
+    /// # Ok(()) }
+    /// ```
+    pub fn scan_async_read<R>(self: Arc<Self>, reader: R) -> AsyncStreamScanner
+    where
+        R: tokio::io::AsyncRead + Send + Unpin + 'static,
+    {
+        AsyncStreamScanner::spawn(self, ReaderChunks::new(reader))
+    }
+
+    /// Scan an async stream of byte chunks (such as a `Stream<Item = Result<Bytes, E>>`
+    /// request body), as an async stream of positioned matches.
+    ///
+    /// See [`Filter::scan_async_read`]. Chunks may split words and UTF-8 characters
+    /// anywhere; an error from the source is reported after the matches before it.
+    pub fn scan_async_stream<S, B, E>(self: Arc<Self>, stream: S) -> AsyncStreamScanner
+    where
+        S: futures_core::Stream<Item = Result<B, E>> + Send + Unpin + 'static,
+        B: AsRef<[u8]> + Send + 'static,
+        E: Into<io::Error> + Send + 'static,
+    {
+        AsyncStreamScanner::spawn(self, stream)
+    }
+
     /// Load a dictionary from a URL without blocking. Requires the `net-async` feature.
     ///
     /// # Examples
@@ -725,6 +2082,242 @@ mod tests {
         assert_eq!(filter.replace("前缀赌博中间色情后缀", '*'), "前缀**中间**后缀");
     }
 
+    #[test]
+    fn test_replace_with_strategy_same_on_every_algorithm() {
+        let words = ["赌", "赌博", "赌博机", "色情"];
+        let text = "赌博机和赌博还有色情";
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            let mut filter = Filter::new();
+            filter.add_words(&words);
+            let patterns = filter.compiled.engine.get_patterns().to_vec();
+            filter.compiled_mut().engine.rebuild_with_algorithm(&patterns, algorithm);
+
+            assert_eq!(filter.replace(text, '*'), "***和**还有**", "{algorithm}");
+            assert_eq!(
+                filter.replace_with_strategy(text, &ReplaceStrategy::Token("<x>".to_string())),
+                "<x>和<x>还有<x>",
+                "{algorithm}"
+            );
+            assert_eq!(
+                filter.replace_with_strategy(text, &ReplaceStrategy::FixedLength { mask: '#', len: 3 }),
+                "###和###还有###",
+                "{algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_category_tracks_folded_form_and_deletion() {
+        let mut filter = Filter::new();
+        filter.add_words_with_category(&["A 级"], "adult");
+        assert_eq!(filter.category_of("A 级"), Some("adult"));
+        assert_eq!(filter.category_of("A级"), Some("adult"));
+
+        filter.del_word("A 级");
+        assert_eq!(filter.category_of("A级"), None);
+    }
+
+    #[test]
+    fn test_find_first_match_span_covers_noise() {
+        let mut filter = Filter::new();
+        filter.add_word("赌博");
+
+        // "@#" is stripped before matching, but the span indexes the caller's text.
+        let text = "!含有赌@#博!";
+        let m = filter.find_first_match(text).unwrap();
+        assert_eq!(&text[m.start..m.end], "赌@#博");
+    }
+
+    #[test]
+    fn test_find_iter_spans_and_ids() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌博", "色情"]);
+
+        let text = "含有赌@博和色情";
+        let found: Vec<(&str, usize, &str)> =
+            filter.find_iter(text).map(|m| (m.pattern, m.pattern_id, &text[m.start..m.end])).collect();
+        assert_eq!(found, vec![("赌博", 0, "赌@博"), ("色情", 1, "色情")]);
+        assert_eq!(filter.find_iter("seqing").count(), 0);
+    }
+
+    #[test]
+    fn test_find_occurrences_keeps_repeats() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌", "赌博", "色情"]);
+
+        let text = "赌博!赌 seqing 赌博";
+        let occurrences = filter.find_occurrences(text);
+        let found: Vec<(&str, bool, &str)> =
+            occurrences.iter().map(|m| (m.word.as_str(), m.is_variant, &text[m.start..m.end])).collect();
+        assert_eq!(
+            found,
+            vec![("赌博", false, "赌博"), ("赌", false, "赌"), ("色情", true, "seqing"), ("赌博", false, "赌博")]
+        );
+
+        let counts = filter.word_counts(text);
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![("色情".to_string(), 1), ("赌".to_string(), 1), ("赌博".to_string(), 2)]
+        );
+        assert!(filter.word_counts("clean").is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_mode_on_every_algorithm() {
+        let words = ["赌", "赌博", "赌博机", "色情"];
+        let text = "赌博机!色情";
+        let exact = |filter: &Filter| -> Vec<(String, usize, usize)> {
+            filter
+                .find_occurrences(text)
+                .into_iter()
+                .filter(|m| !m.is_variant)
+                .map(|m| (m.word, m.start, m.end))
+                .collect()
+        };
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            let mut filter = Filter::new();
+            filter.add_words(&words);
+            filter.compiled_mut().engine.rebuild_with_algorithm(&words.map(String::from), algorithm);
+            assert_eq!(exact(&filter), vec![("赌博机".to_string(), 0, 9), ("色情".to_string(), 10, 16)], "{algorithm}");
+
+            filter.set_overlapping(true);
+            assert_eq!(
+                exact(&filter),
+                vec![
+                    ("赌博机".to_string(), 0, 9),
+                    ("赌博".to_string(), 0, 6),
+                    ("赌".to_string(), 0, 3),
+                    ("色情".to_string(), 10, 16)
+                ],
+                "{algorithm}"
+            );
+            assert_eq!(filter.compiled.engine.find_all("赌博机"), vec!["赌", "赌博", "赌博机"], "{algorithm}");
+            // Replacement stays non-overlapping.
+            assert_eq!(filter.replace(text, '*'), "*****", "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn test_with_algorithm_survives_adding_words() {
+        let mut filter = Filter::with_algorithm(MatchAlgorithm::Regex);
+        filter.add_words(&["赌博", "色情"]);
+        assert_eq!(filter.current_algorithm(), MatchAlgorithm::Regex);
+        assert_eq!(filter.find_in("含有赌博"), (true, "赌博".to_string()));
+    }
+
+    #[test]
+    fn test_words_added_one_at_a_time_match_batch() {
+        let words: Vec<String> = (0..300).map(|i| format!("词{i}")).chain(["赌 博".to_string()]).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let mut batch = Filter::new();
+        batch.add_words(&words);
+        batch.del_words(&["词7", "赌 博"]);
+
+        let mut single = Filter::new();
+        for word in &words {
+            single.add_word(word);
+        }
+        single.del_word("词7");
+        single.del_word("赌 博");
+
+        assert_eq!(single.compiled.engine.get_patterns(), batch.compiled.engine.get_patterns());
+        let text = "词7词77 赌博 词299";
+        assert_eq!(single.find_occurrences(text), batch.find_occurrences(text));
+        assert_eq!(single.replace(text, '*'), batch.replace(text, '*'));
+    }
+
+    #[test]
+    fn test_match_kind_same_on_every_algorithm() {
+        let words = ["赌", "赌博", "赌博机", "博机", "机器"];
+        let text = "赌博机器和赌博";
+        let mut expected: Vec<(MatchKind, String)> = Vec::new();
+        for algorithm in [MatchAlgorithm::AhoCorasick, MatchAlgorithm::WuManber, MatchAlgorithm::Regex] {
+            let mut filter = Filter::with_algorithm(algorithm);
+            filter.add_words(&words);
+            filter.add_words_with_priority(&["机器"], 5);
+            for kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst, MatchKind::Priority] {
+                filter.set_match_kind(kind);
+                let out = filter.replace_with_strategy(text, &ReplaceStrategy::Token("#".into()));
+                match expected.iter().find(|(k, _)| *k == kind) {
+                    Some((_, first)) => assert_eq!(&out, first, "{algorithm} {kind:?}"),
+                    None => expected.push((kind, out)),
+                }
+            }
+        }
+        assert_eq!(
+            expected,
+            vec![
+                (MatchKind::LeftmostLongest, "#器和#".to_string()),
+                (MatchKind::LeftmostFirst, "##器和#博".to_string()),
+                (MatchKind::Priority, "##和#".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_occurrences_follows_match_kind() {
+        let text = "赌博机器和赌博机";
+        for kind in [MatchKind::LeftmostLongest, MatchKind::LeftmostFirst, MatchKind::Priority] {
+            let mut filter = Filter::new();
+            filter.set_match_kind(kind);
+            filter.add_words(&["赌博", "赌博机"]);
+            filter.add_words_with_priority(&["博机器"], 10);
+
+            let exact: Vec<(String, bool, usize, usize)> =
+                filter.find_iter(text).map(|m| (m.pattern.to_string(), false, m.start, m.end)).collect();
+            let found: Vec<(String, bool, usize, usize)> =
+                filter.find_occurrences(text).into_iter().map(|m| (m.word, m.is_variant, m.start, m.end)).collect();
+            assert_eq!(found, exact, "{kind:?}");
+
+            // Variants only fill the gaps between exact matches.
+            #[cfg(feature = "std")]
+            {
+                let found = filter.find_occurrences("dubo 赌博");
+                assert_eq!(found.iter().map(|m| (m.is_variant, m.start)).collect::<Vec<_>>(), [(true, 0), (false, 5)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace_with_closure() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌", "赌博机", "色情"]);
+
+        let text = "赌博机和色情";
+        let out = filter.replace_with(text, |m| {
+            if m.word == "色情" { Cow::Borrowed(&text[m.start..m.end]) } else { Cow::Owned(format!("<{}>", m.word)) }
+        });
+        assert_eq!(out, "<赌博机>和色情");
+        // Exact only: variants are left untouched.
+        assert_eq!(filter.replace_with("seqing", |_| Cow::Borrowed("x")), "seqing");
+    }
+
+    #[test]
+    fn test_replace_with_variants_spans() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌博", "色情"]);
+
+        let mut seen = Vec::new();
+        let text = "含有dubo和色情";
+        let out = filter.replace_with_variants(text, |m| {
+            seen.push((text[m.start..m.end].to_string(), m.is_variant));
+            Cow::Borrowed("*")
+        });
+        assert_eq!(out, "含有*和*");
+        assert_eq!(seen, vec![("dubo".to_string(), true), ("色情".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_annotate_keeps_original_text() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌博", "色情"]);
+
+        let markup = Markup::Custom { open: "<{kind}>".to_string(), close: "</>".to_string() };
+        // Noise stays in the output; the exact match spans the noise inside it.
+        assert_eq!(filter.annotate("【赌-博】和 seqing!", &markup), "【<exact>赌-博</>】和 <variant>seqing</>!");
+        assert_eq!(filter.annotate("正常内容", &Markup::Html), "正常内容");
+    }
+
     #[test]
     fn test_variant_detection() {
         let mut filter = Filter::new();
@@ -738,7 +2331,10 @@ mod tests {
         let mut filter = Filter::new();
         filter.add_words(&["赌博", "色情"]);
 
-        assert_eq!(filter.find_first_match("含有赌博"), Some(Match { word: "赌博".to_string(), is_variant: false }));
+        assert_eq!(
+            filter.find_first_match("含有赌博"),
+            Some(Match { word: "赌博".to_string(), is_variant: false, start: 6, end: 12, layer: None })
+        );
         assert_eq!(filter.find_first_match("正常文本"), None);
     }
 
@@ -748,7 +2344,22 @@ mod tests {
         filter.add_word("赌博");
 
         // Pinyin variant path: word found, but is_variant = true.
-        assert_eq!(filter.find_first_match("含有 dubo"), Some(Match { word: "赌博".to_string(), is_variant: true }));
+        assert_eq!(
+            filter.find_first_match("含有 dubo"),
+            Some(Match { word: "赌博".to_string(), is_variant: true, start: 7, end: 11, layer: None })
+        );
+    }
+
+    #[test]
+    fn test_find_first_match_takes_earliest_variant() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌博", "色情"]);
+
+        // 色情 is detected too, but its variant comes later in the text.
+        let found = filter.find_first_match("dubo 和 seqing").unwrap();
+        assert_eq!((found.word.as_str(), found.is_variant, found.start, found.end), ("赌博", true, 0, 4));
+        let found = filter.find_first_match("seqing 和 dubo").unwrap();
+        assert_eq!((found.word.as_str(), found.start, found.end), ("色情", 0, 6));
     }
 
     #[test]
@@ -757,7 +2368,10 @@ mod tests {
         filter.add_word("赌博");
 
         // Exact hit wins even though a pinyin variant would also match.
-        assert_eq!(filter.find_first_match("赌博 dubo"), Some(Match { word: "赌博".to_string(), is_variant: false }));
+        assert_eq!(
+            filter.find_first_match("赌博 dubo"),
+            Some(Match { word: "赌博".to_string(), is_variant: false, start: 0, end: 6, layer: None })
+        );
     }
 
     #[test]
@@ -765,14 +2379,14 @@ mod tests {
         // Use Wu-Manber in small quantities
         let mut small = Filter::new();
         small.add_words(&["a", "b", "c"]);
-        assert!(matches!(small.engine.current_algorithm(), MatchAlgorithm::WuManber));
+        assert!(matches!(small.compiled.engine.current_algorithm(), MatchAlgorithm::WuManber));
 
         // Aho-Corasick for medium quantity
         let words: Vec<_> = (0..150).map(|i| format!("word{i}")).collect();
         let mut medium = Filter::new();
         medium.add_words(&words.iter().map(|s| s.as_str()).collect::<Vec<_>>());
-        println!("Medium current_algorithm: {:?}", medium.engine.current_algorithm());
-        assert!(matches!(medium.engine.current_algorithm(), MatchAlgorithm::AhoCorasick));
+        println!("Medium current_algorithm: {:?}", medium.compiled.engine.current_algorithm());
+        assert!(matches!(medium.compiled.engine.current_algorithm(), MatchAlgorithm::AhoCorasick));
     }
 
     #[test]
@@ -843,7 +2457,7 @@ mod tests {
         medium.add_words(&word_refs);
 
         println!("Medium (150 words): {:?}", medium.current_algorithm());
-        println!("Pattern count: {}", medium.engine.get_patterns().len());
+        println!("Pattern count: {}", medium.compiled.engine.get_patterns().len());
 
         // Verification algorithm selection logic
         let recommended = MultiPatternEngine::recommend_algorithm(150);
@@ -908,6 +2522,159 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_clones_share_dictionary_until_changed() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Filter>();
+
+        let mut filter = Filter::new();
+        filter.add_word("赌博");
+        let mut tokens = filter.clone();
+        tokens.set_replace_strategy(ReplaceStrategy::Token("[x]".into()));
+        tokens.update_noise_pattern(r"\s").unwrap();
+        assert!(Arc::ptr_eq(&filter.compiled, &tokens.compiled));
+        assert_eq!(filter.redact("赌@博"), "**");
+        assert_eq!(tokens.redact("赌 博"), "[x]");
+        // Results cached under one noise pattern are not served under the other.
+        assert_eq!(filter.find_all("赌@博"), ["赌博"]);
+        assert!(tokens.find_all("赌@博").is_empty());
+
+        // Changing the clone's dictionary copies it; the original is unaffected.
+        tokens.add_word("色情");
+        assert!(!Arc::ptr_eq(&filter.compiled, &tokens.compiled));
+        assert_eq!(filter.find_all("赌博色情"), ["赌博"]);
+        assert_eq!(tokens.find_all("赌博色情").len(), 2);
+    }
+
+    #[test]
+    fn test_entries_carry_metadata() {
+        let mut filter = Filter::new();
+        let mut exact = DictEntry::new("赌博");
+        (exact.variants, exact.severity, exact.replacement) = (false, Some(2), Some("[赌]".into()));
+        let mut spaced = DictEntry::new("色 情");
+        (spaced.category, spaced.priority) = (Some("porn".into()), Some(5));
+        filter.add_entries(&[exact, spaced]);
+
+        assert_eq!(filter.severity_of("赌博"), Some(2));
+        assert_eq!(filter.category_of("色情"), Some("porn"));
+        assert_eq!(filter.priority_of("色情"), 5);
+        assert_eq!(filter.redact("赌博和色情"), "[赌]和**");
+        // Variant detection is off for the exact-only entry.
+        assert!(filter.find_all("dubo").is_empty());
+        assert!(!filter.find_all("seqing").is_empty());
+
+        filter.del_word("赌博");
+        assert_eq!((filter.severity_of("赌博"), filter.replacement_of("赌博")), (None, None));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_replace_layer_keeps_variant_index_in_step() {
+        let index = |filter: &Filter| {
+            let mut e = Encoder::default();
+            filter.compiled.variant_detector.encode(&mut e);
+            e.into_bytes()
+        };
+        let mut fresh = Filter::new();
+        fresh.add_layer("remote", &["赌博", "代开发票"]);
+
+        // Each refresh indexes nothing twice, and words dropped upstream leave the index.
+        let mut filter = Filter::new();
+        filter.add_layer("remote", &["赌博", "色情"]);
+        for _ in 0..3 {
+            filter.replace_layer("remote", &["赌博", "代开发票"]);
+            assert_eq!(index(&filter), index(&fresh));
+        }
+        filter.del_words(&["代开发票"]);
+        fresh.del_words(&["代开发票"]);
+        let mut only = Filter::new();
+        only.add_layer("remote", &["赌博"]);
+        assert_eq!((index(&filter), index(&fresh)), (index(&only), index(&only)));
+        assert_eq!(filter.find_all("dubo seqing"), ["赌博"]);
+    }
+
+    #[test]
+    fn test_layers_toggle_and_report() {
+        let mut filter = Filter::new();
+        filter.add_word("赌博");
+        filter.add_layer("base", &["赌博", "色情"]);
+        filter.add_layer("event", &["赌博机", "色情"]);
+
+        let first = filter.find_first_match("买赌博机").unwrap();
+        assert_eq!((first.word.as_str(), first.layer.as_deref()), ("赌博机", Some("event")));
+        assert_eq!(filter.find_occurrences("色情")[0].layer.as_deref(), Some("base"));
+
+        // A disabled longer word does not hide the shorter one inside it.
+        let mut masked = filter.clone();
+        masked.set_layer_enabled("event", false);
+        assert_eq!(masked.find_all("买赌博机"), ["赌博"]);
+        assert_eq!(masked.replace("买赌博机", '*'), "买**机");
+        let words = |f: &Filter| f.find_occurrences("买赌博机").into_iter().map(|m| m.word).collect::<Vec<_>>();
+        assert_eq!((words(&masked), words(&filter)), (vec!["赌博".to_string()], vec!["赌博机".to_string()]));
+        assert_eq!(filter.with_layers(&["event"]).find_occurrences("色情")[0].layer.as_deref(), Some("event"));
+        assert!(!masked.set_layer_enabled("missing", false));
+
+        // Removing a layer keeps words still in another layer or added directly.
+        assert!(filter.remove_layer("base"));
+        assert_eq!(filter.find_all("赌博 色情").len(), 2);
+        assert!(filter.remove_layer("event"));
+        assert_eq!(filter.find_all("赌博机 色情"), ["赌博"]);
+        assert_eq!(filter.find_first_match("赌博").unwrap().layer, None);
+        assert!(!filter.remove_layer("event"));
+
+        // Replacing a layer drops its old words but keeps direct ones and its on/off state.
+        filter.add_layer("remote", &["赌博", "色情"]);
+        filter.set_layer_enabled("remote", false);
+        filter.replace_layer("remote", &["博彩"]);
+        assert_eq!(filter.layer_words("remote").unwrap(), ["博彩"]);
+        assert!(!filter.is_layer_enabled("remote"));
+        assert!(filter.find_in("赌博").0 && !filter.find_in("博彩").0);
+        assert!(filter.set_layer_enabled("remote", true));
+        assert_eq!((filter.find_in("色情").0, filter.find_in("博彩").0), (false, true));
+    }
+
+    #[test]
+    fn test_load_word_dict_directory() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("sensitive-rs-dicts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("fraud.txt"), "代开发票\n")?;
+        std::fs::write(dir.join("extra.csv"), "word,category\n博彩,gambling\n")?;
+        std::fs::write(dir.join("padded.txt"), "赌博机 \n\n\t六合彩\n")?;
+        #[cfg(feature = "dict-gzip")]
+        {
+            use std::io::Write;
+            let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            gzip.write_all("赌博\n博彩\n".as_bytes())?;
+            std::fs::write(dir.join("gambling.txt.gz"), gzip.finish()?)?;
+        }
+        #[cfg(feature = "dict-zstd")]
+        {
+            let level = ruzstd::encoding::CompressionLevel::Fastest;
+            std::fs::write(dir.join("porn.txt.zst"), ruzstd::encoding::compress_to_vec("色情\n".as_bytes(), level))?;
+        }
+
+        let mut filter = Filter::new();
+        filter.load_word_dict(&dir)?;
+        assert_eq!(filter.category_of("代开发票"), Some("fraud"));
+        #[cfg(feature = "dict-gzip")]
+        assert_eq!((filter.category_of("博彩"), filter.layer_of("赌博")), (Some("gambling"), Some("gambling")));
+        #[cfg(feature = "dict-zstd")]
+        assert_eq!(filter.find_first_match("色情").unwrap().layer.as_deref(), Some("porn"));
+        assert!(!filter.layers().any(|layer| layer == "extra"));
+        // Words are trimmed and blank lines skipped.
+        assert_eq!((filter.category_of("赌博机"), filter.category_of("赌博机 ")), (Some("padded"), None));
+        assert_eq!(filter.category_of("六合彩"), Some("padded"));
+        assert!(!filter.find_all("赌博机x色情").iter().any(String::is_empty));
+        assert!(filter.remove_layer("fraud"));
+        assert!(!filter.find_in("代开发票").0);
+
+        // A bad file fails the whole load and is named in the error.
+        std::fs::write(dir.join("zz.txt"), b"ok\n\xff\n")?;
+        let error = Filter::new().load_word_dict(dir.join("*.txt")).unwrap_err().to_string();
+        assert!(error.contains("zz.txt: line 2"), "{error}");
+        std::fs::remove_dir_all(&dir)
+    }
+
     #[test]
     fn test_parallel_search_cross_boundary() {
         let mut filter = Filter::new();
@@ -0,0 +1,174 @@
+//! Annotated (highlighted) output for review tools.
+//!
+//! [`Markup`] describes how [`Filter::annotate`](crate::Filter::annotate) wraps each match in
+//! the original text: HTML `<mark>` elements, ANSI colours for terminals, or custom markers.
+
+use crate::filter::Match;
+use alloc::string::String;
+
+/// Markers written around each match by [`Filter::annotate`](crate::Filter::annotate).
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, Markup};
+///
+/// let mut filter = Filter::new();
+/// filter.add_word("赌博");
+///
+/// assert_eq!(
+///     filter.annotate("<b>赌博</b>", &Markup::Html),
+///     r#"&lt;b&gt;<mark data-word="赌博" data-kind="exact">赌博</mark>&lt;/b&gt;"#
+/// );
+/// assert_eq!(
+///     filter.annotate("含有赌博", &Markup::Custom { open: "[".into(), close: "]".into() }),
+///     "含有[赌博]"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Markup {
+    /// `<mark data-word="…" data-kind="exact|variant">…</mark>`; all other text is HTML-escaped,
+    /// so the output can be inserted into a page as-is.
+    Html,
+    /// ANSI SGR colours for terminals, e.g. `"31"` (red) for exact and `"33"` (yellow) for
+    /// variant matches. See [`Markup::ansi`] for those defaults.
+    Ansi {
+        /// SGR parameters for exact matches.
+        exact: String,
+        /// SGR parameters for variant matches.
+        variant: String,
+    },
+    /// Literal markers; `{word}` (dictionary form) and `{kind}` (`exact` / `variant`) are
+    /// substituted in both. Nothing is escaped.
+    Custom {
+        /// Written before each match.
+        open: String,
+        /// Written after each match.
+        close: String,
+    },
+}
+
+impl Markup {
+    /// ANSI colours: red for exact matches, yellow for variants.
+    pub fn ansi() -> Self {
+        Self::Ansi { exact: "31".into(), variant: "33".into() }
+    }
+
+    /// Write `text` with every match (sorted, non-overlapping, spans into `text`) wrapped.
+    pub(crate) fn render<'m>(&self, text: &str, matches: impl IntoIterator<Item = &'m Match>) -> String {
+        let mut out = String::with_capacity(text.len() * 2);
+        let mut cursor = 0usize;
+        for m in matches {
+            self.push_text(&mut out, &text[cursor..m.start]);
+            let kind = if m.is_variant { "variant" } else { "exact" };
+            match self {
+                Self::Html => {
+                    out.push_str("<mark data-word=\"");
+                    push_html_escaped(&mut out, &m.word);
+                    out.push_str("\" data-kind=\"");
+                    out.push_str(kind);
+                    out.push_str("\">");
+                    push_html_escaped(&mut out, &text[m.start..m.end]);
+                    out.push_str("</mark>");
+                }
+                Self::Ansi { exact, variant } => {
+                    out.push_str("\x1b[");
+                    out.push_str(if m.is_variant { variant } else { exact });
+                    out.push('m');
+                    out.push_str(&text[m.start..m.end]);
+                    out.push_str("\x1b[0m");
+                }
+                Self::Custom { open, close } => {
+                    push_marker(&mut out, open, &m.word, kind);
+                    out.push_str(&text[m.start..m.end]);
+                    push_marker(&mut out, close, &m.word, kind);
+                }
+            }
+            cursor = m.end;
+        }
+        self.push_text(&mut out, &text[cursor..]);
+        out
+    }
+
+    /// Write unmatched text, escaped if the markup needs it.
+    fn push_text(&self, out: &mut String, text: &str) {
+        match self {
+            Self::Html => push_html_escaped(out, text),
+            Self::Ansi { .. } | Self::Custom { .. } => out.push_str(text),
+        }
+    }
+}
+
+/// Write `marker` with `{word}` and `{kind}` substituted in one pass, so a placeholder
+/// inside the inserted word is left alone.
+fn push_marker(out: &mut String, marker: &str, word: &str, kind: &str) {
+    let mut rest = marker;
+    while let Some(at) = rest.find('{') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        if let Some(after) = rest.strip_prefix("{word}") {
+            out.push_str(word);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{kind}") {
+            out.push_str(kind);
+            rest = after;
+        } else {
+            out.push('{');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+}
+
+fn push_html_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn exact(word: &str, start: usize, end: usize) -> Match {
+        Match { word: word.to_string(), is_variant: false, start, end, layer: None }
+    }
+
+    #[test]
+    fn test_html_escapes_text_and_attributes() {
+        let text = "a&b\"赌博\"";
+        let m = Match { word: "赌\"博".to_string(), ..exact("", 4, 10) };
+        assert_eq!(
+            Markup::Html.render(text, [&m]),
+            "a&amp;b&quot;<mark data-word=\"赌&quot;博\" data-kind=\"exact\">赌博</mark>&quot;"
+        );
+    }
+
+    #[test]
+    fn test_custom_placeholders_substituted_once() {
+        let markup = Markup::Custom { open: "<{word}|{kind}|{x}>".into(), close: "</{kind}>".into() };
+        let m = Match { word: "{kind}".to_string(), ..exact("", 0, 3) };
+        assert_eq!(markup.render("abc", [&m]), "<{kind}|exact|{x}>abc</exact>");
+    }
+
+    #[test]
+    fn test_ansi_colours_by_kind() {
+        let text = "赌博dubo";
+        let matches = [exact("赌博", 0, 6), Match { is_variant: true, ..exact("赌博", 6, 10) }];
+        assert_eq!(Markup::ansi().render(text, &matches), "\x1b[31m赌博\x1b[0m\x1b[33mdubo\x1b[0m");
+    }
+
+    #[test]
+    fn test_custom_placeholders() {
+        let markup = Markup::Custom { open: "{{{kind}:{word}|".into(), close: "}}".into() };
+        assert_eq!(markup.render("x赌博", [&exact("赌博", 1, 7)]), "x{{exact:赌博|赌博}}");
+    }
+}
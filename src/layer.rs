@@ -0,0 +1,160 @@
+//! Named dictionary layers.
+//!
+//! A layer is a named group of words added and removed as a unit with
+//! [`Filter::add_layer`](crate::Filter::add_layer) and
+//! [`Filter::remove_layer`](crate::Filter::remove_layer). All layers share the filter's one
+//! engine; [`Layers`] records which match forms belong to which layers, so a disabled layer's
+//! words can be left out of matching without recompiling anything.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+/// The layers of a filter's dictionary and the layers each match form belongs to.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layers {
+    layers: Vec<(Arc<str>, Vec<String>)>, // Name -> match forms, in the order layers were added
+    members: HashMap<String, Membership>, // Match form in any layer -> where it came from
+}
+
+#[derive(Debug, Clone)]
+struct Membership {
+    layers: Vec<Arc<str>>, // Layers containing the form, in the order it was added to them
+    direct: bool,          // Also added outside any layer, so it stays when its layers go
+}
+
+impl Layers {
+    /// Layer names, in the order the layers were added.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|(name, _)| &**name)
+    }
+
+    /// The match forms of layer `name`.
+    pub(crate) fn forms(&self, name: &str) -> Option<&[String]> {
+        self.layers.iter().find(|(layer, _)| &**layer == name).map(|(_, forms)| forms.as_slice())
+    }
+
+    /// Add `forms` to layer `name`, creating it after the existing layers if needed. A form
+    /// not yet in any layer counts as added directly if `in_dictionary` says it is already
+    /// in the dictionary.
+    pub(crate) fn add(&mut self, name: &str, forms: &[String], in_dictionary: impl Fn(&str) -> bool) {
+        let index = match self.layers.iter().position(|(layer, _)| &**layer == name) {
+            Some(index) => index,
+            None => {
+                self.layers.push((name.into(), Vec::new()));
+                self.layers.len() - 1
+            }
+        };
+        let (name, layer_forms) = &mut self.layers[index];
+        for form in forms {
+            let membership = self
+                .members
+                .entry_ref(form.as_str())
+                .or_insert_with(|| Membership { layers: Vec::new(), direct: in_dictionary(form) });
+            if !membership.layers.contains(name) {
+                membership.layers.push(name.clone());
+                layer_forms.push(form.clone());
+            }
+        }
+    }
+
+    /// Remove layer `name`, returning its forms that are in no other layer and were not
+    /// added directly, or `None` if there is no such layer.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Vec<String>> {
+        let index = self.layers.iter().position(|(layer, _)| &**layer == name)?;
+        let (name, forms) = self.layers.remove(index);
+        let mut orphaned = Vec::new();
+        for form in forms {
+            let Some(membership) = self.members.get_mut(&form) else { continue };
+            membership.layers.retain(|layer| *layer != name);
+            if membership.layers.is_empty() {
+                if !membership.direct {
+                    orphaned.push(form.clone());
+                }
+                self.members.remove(&form);
+            }
+        }
+        Some(orphaned)
+    }
+
+    /// Replace the forms of layer `name` with `forms`, keeping its place among the layers
+    /// or creating it last. Returns the old forms left in no layer and not added directly.
+    pub(crate) fn replace(
+        &mut self,
+        name: &str,
+        forms: &[String],
+        in_dictionary: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let index = self.layers.iter().position(|(layer, _)| &**layer == name);
+        let orphaned = self.remove(name).unwrap_or_default();
+        let orphaned_set: BTreeSet<&str> = orphaned.iter().map(String::as_str).collect();
+        // A form just orphaned is in the dictionary only through this layer.
+        self.add(name, forms, |form| !orphaned_set.contains(form) && in_dictionary(form));
+        if let Some(index) = index {
+            let layer = self.layers.pop().expect("just added");
+            self.layers.insert(index, layer);
+        }
+        orphaned
+    }
+
+    /// Record that `forms` were added outside any layer.
+    pub(crate) fn mark_direct(&mut self, forms: &[String]) {
+        for form in forms {
+            if let Some(membership) = self.members.get_mut(form) {
+                membership.direct = true;
+            }
+        }
+    }
+
+    /// Forget `forms`, which were deleted from the dictionary.
+    pub(crate) fn forget(&mut self, forms: &[String]) {
+        for form in forms {
+            if let Some(membership) = self.members.remove(form) {
+                for name in membership.layers {
+                    if let Some((_, layer_forms)) = self.layers.iter_mut().find(|(layer, _)| *layer == name) {
+                        layer_forms.retain(|f| f != form);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether `form` is matched while the `disabled` layers are off: it was added directly
+    /// or belongs to an enabled layer.
+    pub(crate) fn is_active(&self, form: &str, disabled: &BTreeSet<String>) -> bool {
+        self.members.get(form).is_none_or(|m| m.direct || m.layers.iter().any(|layer| !disabled.contains(&**layer)))
+    }
+
+    /// The first enabled layer `form` belongs to.
+    pub(crate) fn layer_of(&self, form: &str, disabled: &BTreeSet<String>) -> Option<&str> {
+        self.members.get(form)?.layers.iter().map(|layer| &**layer).find(|layer| !disabled.contains(*layer))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn encode(&self, e: &mut crate::snapshot::Encoder) {
+        e.usize(self.layers.len());
+        for (name, forms) in &self.layers {
+            e.str(name);
+            e.strs(forms);
+        }
+        let mut direct: Vec<&str> =
+            self.members.iter().filter(|(_, m)| m.direct).map(|(form, _)| form.as_str()).collect();
+        direct.sort_unstable();
+        e.strs(&direct);
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn decode(d: &mut crate::snapshot::Decoder<'_>) -> Result<Self, crate::snapshot::SnapshotError> {
+        let mut layers = Self::default();
+        for _ in 0..d.len()? {
+            let name = d.string()?;
+            let forms = d.strings()?;
+            layers.add(&name, &forms, |_| false);
+        }
+        let direct = d.strings()?;
+        layers.mark_direct(&direct);
+        Ok(layers)
+    }
+}
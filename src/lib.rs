@@ -32,17 +32,99 @@
 
 extern crate alloc;
 
+mod builder;
+#[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+mod builtin;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+mod conversation;
+#[cfg(feature = "std")]
+mod dict;
 mod engine;
 mod filter;
+mod highlight;
+mod layer;
+mod lint;
+mod profile;
+#[cfg(feature = "net")]
+mod remote;
+mod replace;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod stream;
 #[cfg(feature = "std")]
 mod variant;
 #[cfg(feature = "wasm")]
 mod wasm;
 
+pub use builder::BuildError;
+pub use builder::FilterBuilder;
+#[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+pub use builtin::Builtin;
+pub use conversation::Conversation;
+pub use conversation::ConversationHit;
+pub use conversation::HitPart;
+#[cfg(feature = "std")]
+pub use dict::DictDiff;
+#[cfg(feature = "std")]
+pub use dict::DictEntry;
+#[cfg(feature = "std")]
+pub use dict::DictError;
+#[cfg(feature = "std")]
+pub use dict::DictFormat;
+#[cfg(feature = "std")]
+pub use dict::Precedence;
+#[cfg(feature = "std")]
+pub use dict::canonicalize;
+#[cfg(feature = "std")]
+pub use dict::diff_entries;
+#[cfg(feature = "std")]
+pub use dict::format_entries;
+#[cfg(feature = "std")]
+pub use dict::merge_entries;
+#[cfg(feature = "std")]
+pub use dict::parse_entries;
+#[cfg(feature = "std")]
+pub use dict::read_entries;
+#[cfg(feature = "std")]
+pub use dict::read_word_lists;
+pub use engine::FindIter;
 pub use engine::MatchAlgorithm;
+pub use engine::MatchKind;
+pub use engine::MatchRef;
 pub use engine::MultiPatternEngine;
+pub use engine::StaticDict;
 pub use filter::Filter;
 pub use filter::Match;
+pub use highlight::Markup;
+pub use lint::DictIssue;
+pub use lint::DictionaryReport;
+pub use lint::IssueKind;
+pub use profile::Profile;
+#[cfg(feature = "net")]
+pub use remote::Refresh;
+#[cfg(feature = "net")]
+pub use remote::Refresher;
+#[cfg(feature = "net")]
+pub use remote::RemoteDict;
+pub use replace::ReplaceStrategy;
+#[cfg(feature = "std")]
+pub use shared::SharedFilter;
+#[cfg(feature = "std")]
+pub use snapshot::SNAPSHOT_VERSION;
+#[cfg(feature = "std")]
+pub use snapshot::SnapshotError;
+#[cfg(feature = "async-io")]
+pub use stream::AsyncStreamScanner;
+#[cfg(feature = "std")]
+pub use stream::Scanner;
+#[cfg(feature = "std")]
+pub use stream::StreamMatch;
+#[cfg(feature = "std")]
+pub use stream::StreamScanner;
 #[cfg(feature = "std")]
 pub use variant::VariantDetector;
 
@@ -0,0 +1,208 @@
+//! Dictionary quality checks.
+//!
+//! [`Filter::lint`](crate::Filter::lint) checks a word list against the filter's settings and
+//! returns a [`DictionaryReport`]: entries that can never match because noise removal strips
+//! characters they contain, entries made redundant by a shorter entry inside them, entries
+//! that normalize to the same match form as an earlier one, and entries too short to be
+//! useful.
+
+use crate::filter::Filter;
+use aho_corasick::AhoCorasick;
+use alloc::string::String;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+/// The result of [`Filter::lint`](crate::Filter::lint).
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, IssueKind};
+///
+/// let filter = Filter::new();
+/// let report = filter.lint(&["赌博", "赌博机", "赌-博", "色 情", "色情", "x"]);
+///
+/// let kinds: Vec<_> = report.issues.iter().map(|issue| (issue.word.as_str(), &issue.kind)).collect();
+/// assert_eq!(kinds, [
+///     ("赌博机", &IssueKind::Redundant { contains: "赌博".into() }),
+///     ("赌-博", &IssueKind::Unreachable { normalized: "赌博".into() }),
+///     ("赌-博", &IssueKind::Duplicate { of: "赌博".into() }),
+///     ("色情", &IssueKind::Duplicate { of: "色 情".into() }),
+///     ("x", &IssueKind::TooShort),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct DictionaryReport {
+    /// Number of non-blank entries checked.
+    pub entries: usize,
+    /// Problems found, in order of entry; an entry can have several.
+    pub issues: Vec<DictIssue>,
+}
+
+impl DictionaryReport {
+    /// Whether no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues of the same kind as `kind` (ignoring its fields).
+    pub fn count(&self, kind: &IssueKind) -> usize {
+        self.issues.iter().filter(|issue| core::mem::discriminant(&issue.kind) == core::mem::discriminant(kind)).count()
+    }
+}
+
+/// One problem with one dictionary entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DictIssue {
+    /// Position of the entry in the checked list (one less than its line in a word file).
+    pub index: usize,
+    /// The entry as given.
+    pub word: String,
+    /// What is wrong with it.
+    pub kind: IssueKind,
+}
+
+impl core::fmt::Display for DictIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}: {}", self.index + 1, self.word, self.kind)
+    }
+}
+
+/// What [`DictIssue`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IssueKind {
+    /// The entry contains characters the noise pattern removes from every text, so it can
+    /// never match. `normalized` is the entry without them (empty if nothing is left).
+    Unreachable {
+        /// The entry after noise removal.
+        normalized: String,
+    },
+    /// The entry contains the shorter entry `contains`, so every text it matches is already
+    /// flagged.
+    Redundant {
+        /// The shorter entry.
+        contains: String,
+    },
+    /// After noise removal, trimming and (with whitespace folding on) removing whitespace, the
+    /// entry is the same as the earlier entry `of`.
+    Duplicate {
+        /// The earlier entry.
+        of: String,
+    },
+    /// A single character, or two ASCII characters: matches inside many unrelated words.
+    TooShort,
+}
+
+impl core::fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unreachable { normalized } if normalized.is_empty() => {
+                write!(f, "unreachable: consists of characters removed as noise")
+            }
+            Self::Unreachable { normalized } => write!(f, "unreachable: noise removal leaves `{normalized}`"),
+            Self::Redundant { contains } => write!(f, "redundant: contains `{contains}`"),
+            Self::Duplicate { of } => write!(f, "duplicate of `{of}`"),
+            Self::TooShort => write!(f, "too short"),
+        }
+    }
+}
+
+impl DictionaryReport {
+    pub(crate) fn new(filter: &Filter, words: &[&str]) -> Self {
+        let mut report = Self::default();
+        let mut first_of_key: HashMap<String, &str> = HashMap::new();
+        // Entries that can match, with the forms they are matched by.
+        let mut reachable: Vec<(usize, &str)> = Vec::new();
+        let mut forms: Vec<(String, usize)> = Vec::new();
+
+        for (index, &word) in words.iter().enumerate() {
+            if word.trim().is_empty() {
+                continue;
+            }
+            report.entries += 1;
+            let mut issue = |kind| report.issues.push(DictIssue { index, word: word.into(), kind });
+
+            let normalized = filter.remove_noise(word);
+            let unreachable = normalized != word;
+            if unreachable {
+                issue(IssueKind::Unreachable { normalized: normalized.clone() });
+            }
+            let key = if filter.folds_whitespace() {
+                normalized.chars().filter(|c| !c.is_whitespace()).collect()
+            } else {
+                String::from(normalized.trim())
+            };
+            let duplicate = !key.is_empty() && first_of_key.contains_key(&key);
+            if duplicate {
+                issue(IssueKind::Duplicate { of: first_of_key[&key].into() });
+            } else if !key.is_empty() {
+                first_of_key.insert(key, word);
+            }
+            let chars = word.chars().count();
+            if chars == 1 || (chars == 2 && word.is_ascii()) {
+                issue(IssueKind::TooShort);
+            }
+            if !unreachable && !duplicate {
+                for form in filter.word_match_variants(word) {
+                    forms.push((form, reachable.len()));
+                }
+                reachable.push((index, word));
+            }
+        }
+
+        report.find_redundant(&reachable, &forms);
+        report.issues.sort_by_key(|issue| issue.index);
+        report
+    }
+
+    /// Flag each reachable entry containing a match form of another, shorter, entry.
+    fn find_redundant(&mut self, reachable: &[(usize, &str)], forms: &[(String, usize)]) {
+        let Ok(automaton) = AhoCorasick::new(forms.iter().map(|(form, _)| form)) else { return };
+        for (entry, &(index, word)) in reachable.iter().enumerate() {
+            let contained = automaton
+                .find_overlapping_iter(word)
+                .map(|m| forms[m.pattern().as_usize()].1)
+                .find(|&other| other != entry && reachable[other].1.len() < word.len());
+            if let Some(other) = contained {
+                let kind = IssueKind::Redundant { contains: reachable[other].1.into() };
+                self.issues.push(DictIssue { index, word: word.into(), kind });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_flags_each_kind() {
+        let mut filter = Filter::new();
+        let report = filter.lint(&["赌博", "", "赌博机", "赌博!", "!!!", " 赌博", "he", "she", "色 情", "色情"]);
+        assert_eq!(report.entries, 9);
+        let found: Vec<_> = report.issues.iter().map(|issue| (issue.index, issue.kind.clone())).collect();
+        assert_eq!(
+            found,
+            [
+                (2, IssueKind::Redundant { contains: "赌博".into() }),
+                (3, IssueKind::Unreachable { normalized: "赌博".into() }),
+                (3, IssueKind::Duplicate { of: "赌博".into() }),
+                (4, IssueKind::Unreachable { normalized: String::new() }),
+                (5, IssueKind::Duplicate { of: "赌博".into() }),
+                (6, IssueKind::TooShort),
+                (7, IssueKind::Redundant { contains: "he".into() }),
+                (9, IssueKind::Duplicate { of: "色 情".into() }),
+            ]
+        );
+        assert_eq!(report.count(&IssueKind::TooShort), 1);
+
+        // Whether an entry is reachable depends on the noise pattern.
+        filter.update_noise_pattern(r"[\s!]").unwrap();
+        let report = filter.lint(&["赌博", "赌 博"]);
+        assert_eq!(report.issues[0].kind, IssueKind::Unreachable { normalized: "赌博".into() });
+        assert!(filter.lint(&["赌博", "色情"]).is_clean());
+    }
+}
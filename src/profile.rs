@@ -0,0 +1,378 @@
+//! Per-tenant dictionaries on top of one shared base.
+//!
+//! Many tenants each adding a few words to the same large dictionary would otherwise need
+//! a full [`Filter`] apiece. A [`Profile`] references the shared base through an `Arc` and
+//! compiles only its own additions; exemptions switch base words off for the profile
+//! without touching the base.
+
+use crate::engine::resolve_by_kind;
+use crate::filter::{CleanText, Filter, Match, fill_gaps};
+use crate::replace::{self, ReplaceStrategy};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+use hashbrown::HashSet;
+
+#[cfg(feature = "std")]
+use crate::dict::DictEntry;
+
+/// A shared base [`Filter`] plus one tenant's own words and exemptions.
+///
+/// The base is never copied: any number of profiles hold the same `Arc<Filter>`, and each
+/// compiles only its overlay, a small filter with the base's settings (noise pattern,
+/// whitespace folding, match kind, overlapping mode and variant channels). Lookups search
+/// both dictionaries and resolve the matches together by the base's
+/// [`MatchKind`](crate::MatchKind), with the priorities each dictionary gives its words and
+/// overlay words counting as added after base words; where both have a word at the same
+/// span, the overlay's metadata is used. An exempt base word is left out of matching
+/// altogether, so it does not hide a shorter word inside it.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, Profile};
+/// use std::sync::Arc;
+///
+/// let mut base = Filter::new();
+/// base.add_words(&["赌博", "赌博机", "色情"]);
+/// let base = Arc::new(base);
+///
+/// let mut arcade = Profile::new(Arc::clone(&base));
+/// arcade.exempt_words(&["赌博机"]);
+/// arcade.add_words(&["代开发票"]);
+///
+/// assert_eq!(arcade.find_all("赌博机 代开发票"), ["代开发票", "赌博"]);
+/// assert_eq!(arcade.replace("赌博机", '*'), "**机");
+/// assert_eq!(base.replace("赌博机 代开发票", '*'), "*** 代开发票");
+/// ```
+#[derive(Clone)]
+pub struct Profile {
+    base: Arc<Filter>,
+    overlay: Filter,
+    exempt: HashSet<String>, // Base match forms this profile does not flag
+    replace_strategy: ReplaceStrategy,
+}
+
+/// Which dictionary a candidate came from; the overlay sorts first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    Overlay,
+    Base,
+}
+
+impl Profile {
+    /// A profile with no words or exemptions of its own over `base`, redacting with the
+    /// base's [`ReplaceStrategy`].
+    pub fn new(base: Arc<Filter>) -> Self {
+        let overlay = base.empty_like();
+        let replace_strategy = base.replace_strategy().clone();
+        Self { base, overlay, exempt: HashSet::new(), replace_strategy }
+    }
+
+    /// The shared base filter.
+    pub fn base(&self) -> &Arc<Filter> {
+        &self.base
+    }
+
+    /// The filter holding this profile's own words.
+    pub fn overlay(&self) -> &Filter {
+        &self.overlay
+    }
+
+    /// Add words of this profile's own (see [`Filter::add_words`]).
+    pub fn add_words(&mut self, words: &[&str]) {
+        self.overlay.add_words(words);
+    }
+
+    /// Add words of this profile's own with a priority for
+    /// [`MatchKind::Priority`](crate::MatchKind::Priority) (see
+    /// [`Filter::add_words_with_priority`]).
+    pub fn add_words_with_priority(&mut self, words: &[&str], priority: i32) {
+        self.overlay.add_words_with_priority(words, priority);
+    }
+
+    /// Add words with metadata of this profile's own (see [`Filter::add_entries`]).
+    #[cfg(feature = "std")]
+    pub fn add_entries(&mut self, entries: &[DictEntry]) {
+        self.overlay.add_entries(entries);
+    }
+
+    /// Remove words this profile added. Base words are exempted with
+    /// [`Profile::exempt_words`] instead.
+    pub fn del_words(&mut self, words: &[&str]) {
+        self.overlay.del_words(words);
+    }
+
+    /// Stop flagging these base words for this profile. Words the profile adds itself are
+    /// still flagged.
+    pub fn exempt_words(&mut self, words: &[&str]) {
+        for word in words {
+            self.exempt.extend(self.base.word_match_variants(word));
+        }
+    }
+
+    /// Flag these base words again.
+    pub fn remove_exemptions(&mut self, words: &[&str]) {
+        for word in words {
+            for form in self.base.word_match_variants(word) {
+                self.exempt.remove(&form);
+            }
+        }
+    }
+
+    /// Whether the base word `word` is exempted for this profile.
+    #[must_use]
+    pub fn is_exempt(&self, word: &str) -> bool {
+        self.base.word_match_variants(word).iter().any(|form| self.exempt.contains(form))
+    }
+
+    /// Set the strategy used by [`Profile::redact`].
+    pub fn set_replace_strategy(&mut self, strategy: ReplaceStrategy) {
+        self.replace_strategy = strategy;
+    }
+
+    /// The category of `word`, from the overlay if it has one there, else from the base.
+    #[must_use]
+    pub fn category_of(&self, word: &str) -> Option<&str> {
+        self.overlay.category_of(word).or_else(|| self.base.category_of(word))
+    }
+
+    /// The replacement text of `word`, from the overlay if it has one there, else from the base.
+    #[must_use]
+    pub fn replacement_of(&self, word: &str) -> Option<&str> {
+        self.overlay.replacement_of(word).or_else(|| self.base.replacement_of(word))
+    }
+
+    /// Every occurrence of a word from either dictionary, in text order, as in
+    /// [`Filter::find_occurrences`].
+    #[must_use]
+    pub fn find_occurrences(&self, text: &str) -> Vec<Match> {
+        let clean = self.base.clean(text);
+        if self.base.is_overlapping() {
+            let mut matches: Vec<Match> = Vec::new();
+            for (span, is_variant, word, source) in self.candidates(&clean.text, true, true) {
+                let (start, end) = clean.input_span(span.start, span.end);
+                let layer = self.filter(source).layer_of(&word).map(String::from);
+                let m = Match { word, is_variant, start, end, layer };
+                if !matches
+                    .iter()
+                    .rev()
+                    .take_while(|seen| seen.start == m.start)
+                    .any(|seen| (&seen.word, seen.end, seen.is_variant) == (&m.word, m.end, m.is_variant))
+                {
+                    matches.push(m);
+                }
+            }
+            return matches;
+        }
+        self.resolve(&clean, true).into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// Every word from either dictionary present in the text, exact or variant,
+    /// deduplicated and sorted, as in [`Filter::find_all`]. Like that method, and unlike
+    /// [`Profile::find_occurrences`], a word is listed even where an overlapping match wins.
+    #[must_use]
+    pub fn find_all(&self, text: &str) -> Vec<String> {
+        let clean_text = self.base.remove_noise(text);
+        let mut words = self.base.words_present(&clean_text, &self.exempt);
+        words.extend(self.overlay.words_present(&clean_text, &HashSet::new()));
+        words.sort_unstable();
+        words.dedup();
+        words
+    }
+
+    /// The first exact match, or failing that the first variant match, as in
+    /// [`Filter::find_first_match`].
+    #[must_use]
+    pub fn find_first_match(&self, text: &str) -> Option<Match> {
+        let occurrences = self.find_occurrences(text);
+        let exact = occurrences.iter().position(|m| !m.is_variant);
+        occurrences.into_iter().nth(exact.unwrap_or(0))
+    }
+
+    /// Returns `(found, word)` for [`Profile::find_first_match`], as in [`Filter::find_in`].
+    #[must_use]
+    pub fn find_in(&self, text: &str) -> (bool, String) {
+        match self.find_first_match(text) {
+            Some(m) => (true, m.word),
+            None => (false, String::new()),
+        }
+    }
+
+    /// Mask exact matches with one `replacement` char per character, as in [`Filter::replace`].
+    #[must_use]
+    pub fn replace(&self, text: &str, replacement: char) -> String {
+        let strategy = ReplaceStrategy::Mask(replacement);
+        self.replace_with(text, |m| strategy.replacement(&m.word, self.category_of(&m.word)))
+    }
+
+    /// Replace exact matches with this profile's [`ReplaceStrategy`], or with the
+    /// replacement text a word was added with, as in [`Filter::redact`].
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        self.replace_with(text, |m| match self.replacement_of(&m.word) {
+            Some(replacement) => Cow::Borrowed(replacement),
+            None => self.replace_strategy.replacement(&m.word, self.category_of(&m.word)),
+        })
+    }
+
+    /// Replace exact matches with text chosen per match, as in [`Filter::replace_with`].
+    #[must_use]
+    pub fn replace_with<'r>(&self, text: &str, mut replacement: impl FnMut(&Match) -> Cow<'r, str>) -> String {
+        let clean = self.base.clean(text);
+        let resolved = self.resolve(&clean, false);
+        replace::rebuild(&clean.text, resolved.iter().map(|(span, m)| (span.clone(), replacement(m))))
+    }
+
+    fn filter(&self, source: Source) -> &Filter {
+        match source {
+            Source::Overlay => &self.overlay,
+            Source::Base => &self.base,
+        }
+    }
+
+    /// Candidates from both dictionaries over the clean text, sorted by start ascending,
+    /// then longest first, then exact before variant, then overlay before base. Variant
+    /// spans whose text is a word of either dictionary are left out.
+    fn candidates(
+        &self,
+        clean_text: &str,
+        include_variants: bool,
+        overlapping: bool,
+    ) -> Vec<(Range<usize>, bool, String, Source)> {
+        let tagged = |filter: &Filter, exempt: &HashSet<String>, source: Source| {
+            let found = filter.match_candidates(clean_text, include_variants, overlapping, exempt);
+            found.into_iter().map(move |(span, is_variant, word)| (span, is_variant, word, source))
+        };
+        let is_word = |span: &Range<usize>| {
+            let text = &clean_text[span.clone()];
+            self.overlay.engine().contains(text) || self.base.engine().contains(text)
+        };
+        let mut candidates: Vec<_> = tagged(&self.base, &self.exempt, Source::Base)
+            .chain(tagged(&self.overlay, &HashSet::new(), Source::Overlay))
+            .filter(|(span, is_variant, _, _)| !is_variant || !is_word(span))
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.0.start.cmp(&b.0.start).then(b.0.end.cmp(&a.0.end)).then(a.1.cmp(&b.1)).then(a.3.cmp(&b.3))
+        });
+        candidates
+    }
+
+    /// Non-overlapping matches from both dictionaries, each paired with its clean-text span.
+    /// Exact matches are resolved by the base's match kind, then variants fill the gaps
+    /// between them.
+    fn resolve(&self, clean: &CleanText<'_>, include_variants: bool) -> Vec<(Range<usize>, Match)> {
+        let (mut exact, variants): (Vec<_>, Vec<_>) = self
+            .candidates(&clean.text, include_variants, true)
+            .into_iter()
+            .partition(|(_, is_variant, _, _)| !is_variant);
+        // One word per span, the overlay's (it sorts first).
+        exact.dedup_by(|later, kept| later.0 == kept.0);
+        let base_len = self.base.engine().get_patterns().len();
+        let rank = |(word, source): &(String, Source)| {
+            let engine = self.filter(*source).engine();
+            let id = engine.pattern_id(word).unwrap_or_default();
+            (engine.priority(word), if *source == Source::Overlay { base_len + id } else { id })
+        };
+        let exact = exact.into_iter().map(|(span, _, word, source)| (span.start, span.end, (word, source))).collect();
+        let exact = resolve_by_kind(self.base.match_kind(), exact, rank)
+            .into_iter()
+            .map(|(start, end, (word, source))| (start..end, false, word, source))
+            .collect();
+        fill_gaps(exact, variants, |(span, _, _, _)| span)
+            .into_iter()
+            .map(|(span, is_variant, word, source)| {
+                let (start, end) = clean.input_span(span.start, span.end);
+                let layer = self.filter(source).layer_of(&word).map(String::from);
+                (span, Match { word, is_variant, start, end, layer })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MatchKind;
+
+    fn base() -> Arc<Filter> {
+        let mut base = Filter::new();
+        base.add_words(&["赌博", "赌博机", "色情"]);
+        Arc::new(base)
+    }
+
+    #[test]
+    fn test_profiles_share_base() {
+        let base = base();
+        let mut first = Profile::new(Arc::clone(&base));
+        let mut second = Profile::new(Arc::clone(&base));
+        first.add_words(&["代开发票"]);
+        second.exempt_words(&["色情"]);
+
+        assert!(Arc::ptr_eq(first.base(), second.base()));
+        assert_eq!(first.overlay().engine().get_patterns().len(), 1);
+        assert_eq!(first.find_all("色情 代开发票"), ["代开发票", "色情"]);
+        assert_eq!(second.find_all("色情 代开发票"), Vec::<String>::new());
+        assert_eq!(base.find_all("色情 代开发票"), ["色情"]);
+
+        second.remove_exemptions(&["色情"]);
+        assert!(!second.is_exempt("色情"));
+        assert_eq!(second.find_in("色情"), (true, "色情".to_string()));
+    }
+
+    #[test]
+    fn test_exempt_word_does_not_hide_shorter_one() {
+        let mut profile = Profile::new(base());
+        profile.exempt_words(&["赌博机"]);
+
+        let found = profile.find_occurrences("买赌博机");
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].word.as_str(), found[0].start, found[0].end), ("赌博", 3, 9));
+        // Pinyin variants of an exempt word are not reported either.
+        assert!(profile.find_all("dubo ji").iter().all(|w| w != "赌博机"));
+    }
+
+    #[test]
+    fn test_match_kind_spans_base_and_overlay() {
+        let mut base = Filter::new();
+        base.set_match_kind(MatchKind::Priority);
+        base.add_words(&["赌博"]);
+        let mut single = base.clone();
+        single.add_words_with_priority(&["博机器"], 10);
+
+        let mut profile = Profile::new(Arc::new(base));
+        profile.add_words_with_priority(&["博机器"], 10);
+        assert_eq!(profile.replace("赌博机器", '*'), "赌***");
+        assert_eq!(profile.replace("赌博机器", '*'), single.replace("赌博机器", '*'));
+        let found = profile.find_occurrences("赌博机器");
+        assert_eq!((found[0].word.as_str(), found[0].is_variant, found[0].start), ("博机器", false, 3));
+    }
+
+    #[test]
+    fn test_find_all_lists_every_word_present() {
+        let mut base = Filter::new();
+        base.add_words(&["赌博", "博机器"]);
+        let base = Arc::new(base);
+        let profile = Profile::new(Arc::clone(&base));
+
+        assert_eq!(profile.find_all("赌博机器"), base.find_all("赌博机器"));
+        #[cfg(feature = "std")]
+        assert_eq!(profile.find_all("赌博机器"), ["博机器", "赌博"]);
+        assert_eq!(profile.find_occurrences("赌博机器").len(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_overlay_metadata_wins() {
+        let mut profile = Profile::new(base());
+        let mut entry = DictEntry::new("赌博");
+        entry.replacement = Some("[赌]".into());
+        profile.add_entries(&[entry, DictEntry::new("博彩")]);
+
+        assert_eq!(profile.redact("赌博和色情"), "[赌]和**");
+        assert_eq!(profile.redact("赌博彩"), "[赌]彩");
+        assert_eq!(profile.base().redact("赌博"), "**");
+    }
+}
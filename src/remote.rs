@@ -0,0 +1,387 @@
+//! Dictionaries kept in sync with a URL.
+//!
+//! [`RemoteDict`] downloads a word list with conditional requests (`If-None-Match` /
+//! `If-Modified-Since`), so an unchanged list costs one `304 Not Modified`, and replaces
+//! the filter layer it owns with each new version, so words deleted upstream are deleted
+//! here too. [`RemoteDict::spawn`] keeps refreshing a [`SharedFilter`] from a background
+//! thread.
+
+use crate::filter::Filter;
+use crate::shared::SharedFilter;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::time::Duration;
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use std::hash::{BuildHasher, RandomState};
+use std::io;
+use std::sync::{Condvar, Mutex};
+use std::thread::JoinHandle;
+
+/// A word list at a URL, loaded into one [layer](Filter::add_layer) of a filter.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sensitive_rs::{Filter, Refresh, RemoteDict};
+/// use std::time::Duration;
+///
+/// let mut remote = RemoteDict::new("https://example.com/dict.txt").timeout(Duration::from_secs(30));
+/// let mut filter = Filter::new();
+///
+/// remote.refresh(&mut filter)?; // downloads the list into the `network` layer
+/// if remote.refresh(&mut filter)? == Refresh::NotModified {
+///     println!("unchanged since {:?}", remote.etag());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteDict {
+    url: String,
+    layer: String,
+    timeout: Duration,
+    etag: Option<String>,          // Validator of the version loaded last
+    last_modified: Option<String>, // Date of the version loaded last
+}
+
+/// What a [`RemoteDict::refresh`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Refresh {
+    /// The server reported the list unchanged; the filter was not touched.
+    NotModified,
+    /// A new version replaced the layer.
+    Updated {
+        /// Words in the new version.
+        words: usize,
+    },
+}
+
+impl RemoteDict {
+    /// The word list at `url`, one word per line, loaded into the layer `network` with a
+    /// 5 second timeout.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            layer: "network".into(),
+            timeout: Duration::from_secs(5),
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Load into the layer `name` instead of `network`.
+    #[must_use]
+    pub fn layer(mut self, name: impl Into<String>) -> Self {
+        self.layer = name.into();
+        self
+    }
+
+    /// Give up on a request after `timeout`, connecting and downloading included.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The URL of the list.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The layer the list is loaded into.
+    pub fn layer_name(&self) -> &str {
+        &self.layer
+    }
+
+    /// The `ETag` of the version loaded last, if the server sent one.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// The `Last-Modified` date of the version loaded last, if the server sent one.
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+
+    /// Download the list if it changed since the version loaded last and make it the
+    /// content of the layer (see [`Filter::replace_layer`]).
+    ///
+    /// # Errors
+    ///
+    /// If the request fails, times out or gets an error status. The filter is unchanged then.
+    pub fn refresh(&mut self, filter: &mut Filter) -> io::Result<Refresh> {
+        Ok(match self.fetch()? {
+            Some(version) => version.apply(self, filter),
+            None => Refresh::NotModified,
+        })
+    }
+
+    /// [`RemoteDict::refresh`] for a filter in use by other threads: the download runs
+    /// while readers keep the current snapshot, then the new layer is published in one
+    /// [`SharedFilter::update`].
+    ///
+    /// # Errors
+    ///
+    /// As [`RemoteDict::refresh`]; nothing is published then.
+    pub fn refresh_shared(&mut self, shared: &SharedFilter) -> io::Result<Refresh> {
+        Ok(match self.fetch()? {
+            Some(version) => shared.update(|filter| version.apply(self, filter)),
+            None => Refresh::NotModified,
+        })
+    }
+
+    /// Refresh `shared` from a background thread every `interval`, plus a random delay of
+    /// up to `jitter` so that many instances do not poll the server in step. The first
+    /// refresh happens at once.
+    ///
+    /// Failed refreshes leave the filter as it was and are retried at the next interval;
+    /// the last error is kept for [`Refresher::take_error`].
+    pub fn spawn(mut self, shared: Arc<SharedFilter>, interval: Duration, jitter: Duration) -> Refresher {
+        let state = Arc::new(RefresherState::default());
+        let thread = {
+            let state = Arc::clone(&state);
+            std::thread::spawn(move || {
+                let random = RandomState::new();
+                let mut round = 0u64;
+                loop {
+                    let result = self.refresh_shared(&shared);
+                    let mut status = state.status.lock().unwrap_or_else(|e| e.into_inner());
+                    status.refreshes += 1;
+                    if let Err(e) = result {
+                        status.error = Some(e);
+                    }
+                    round += 1;
+                    let delay = interval + jitter.mul_f64(random.hash_one(round) as f64 / u64::MAX as f64);
+                    let (status, _) = state
+                        .wake
+                        .wait_timeout_while(status, delay, |status| !status.stopped)
+                        .unwrap_or_else(|e| e.into_inner());
+                    if status.stopped {
+                        return;
+                    }
+                }
+            })
+        };
+        Refresher { state, thread: Some(thread) }
+    }
+
+    /// The new version of the list, or `None` if the server says it is unchanged.
+    fn fetch(&self) -> io::Result<Option<Version>> {
+        // Build a client per call, as `Filter::load_net_word_dict` does, so no blocking
+        // client outlives the call inside an async runtime.
+        let client = reqwest::blocking::Client::builder().timeout(self.timeout).build().map_err(io::Error::other)?;
+        let mut request = client.get(&self.url);
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(date) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, date);
+        }
+        let response = request.send().map_err(io::Error::other)?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(io::Error::other(format!("HTTP request failed: {}", response.status())));
+        }
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+        let body = response.text().map_err(io::Error::other)?;
+        let words = body.lines().map(str::trim).filter(|word| !word.is_empty()).map(String::from).collect();
+        Ok(Some(Version { words, etag, last_modified }))
+    }
+}
+
+/// A downloaded version of the list and its validators.
+struct Version {
+    words: Vec<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Version {
+    /// Replace the layer of `remote` in `filter` and remember the validators.
+    fn apply(&self, remote: &mut RemoteDict, filter: &mut Filter) -> Refresh {
+        let words: Vec<&str> = self.words.iter().map(String::as_str).collect();
+        filter.replace_layer(&remote.layer, &words);
+        remote.etag.clone_from(&self.etag);
+        remote.last_modified.clone_from(&self.last_modified);
+        Refresh::Updated { words: words.len() }
+    }
+}
+
+/// The background thread started by [`RemoteDict::spawn`]. Dropping it stops the thread.
+pub struct Refresher {
+    state: Arc<RefresherState>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct RefresherState {
+    status: Mutex<Status>,
+    wake: Condvar, // Signalled on stop, to cut the wait short
+}
+
+#[derive(Default)]
+struct Status {
+    refreshes: u64,
+    error: Option<io::Error>,
+    stopped: bool,
+}
+
+impl Refresher {
+    /// Refreshes attempted so far, failed ones included.
+    pub fn refreshes(&self) -> u64 {
+        self.state.status.lock().unwrap_or_else(|e| e.into_inner()).refreshes
+    }
+
+    /// The error of the last failed refresh not taken yet.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.state.status.lock().unwrap_or_else(|e| e.into_inner()).error.take()
+    }
+
+    /// Stop refreshing, waiting for a refresh in progress to finish.
+    pub fn stop(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        self.state.status.lock().unwrap_or_else(|e| e.into_inner()).stopped = true;
+        self.state.wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Refresher {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+impl core::fmt::Debug for Refresher {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Refresher").field("refreshes", &self.refreshes()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// A local HTTP server publishing `(etag, body)`, answering `304` to a matching
+    /// `If-None-Match`. Records the conditional header of each request.
+    struct StandIn {
+        url: String,
+        current: Arc<Mutex<(String, String)>>,
+        seen: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl StandIn {
+        fn start(etag: &str, body: &str) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/dict.txt", listener.local_addr().unwrap());
+            let current = Arc::new(Mutex::new((etag.to_string(), body.to_string())));
+            let seen = Arc::new(Mutex::new(Vec::new()));
+            let (served, log) = (Arc::clone(&current), Arc::clone(&seen));
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut if_none_match = None;
+                    let mut reader = BufReader::new(&stream);
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap() > 2 {
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("if-none-match") {
+                                if_none_match = Some(value.trim().to_string());
+                            }
+                        }
+                        line.clear();
+                    }
+                    let (etag, body) = served.lock().unwrap().clone();
+                    let response = if if_none_match.as_deref() == Some(etag.as_str()) {
+                        "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string()
+                    } else {
+                        format!("HTTP/1.1 200 OK\r\nETag: {etag}\r\nContent-Length: {}\r\n\r\n{body}", body.len())
+                    };
+                    log.lock().unwrap().push(if_none_match);
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+            Self { url, current, seen }
+        }
+
+        fn publish(&self, etag: &str, body: &str) {
+            *self.current.lock().unwrap() = (etag.to_string(), body.to_string());
+        }
+    }
+
+    #[test]
+    fn test_refresh_is_conditional_and_replaces_layer() {
+        let server = StandIn::start("\"v1\"", "赌博\n色情\n");
+        let mut remote = RemoteDict::new(&server.url).layer("remote");
+        let mut filter = Filter::new();
+        filter.add_word("代开发票");
+
+        assert_eq!(remote.refresh(&mut filter).unwrap(), Refresh::Updated { words: 2 });
+        assert_eq!(remote.etag(), Some("\"v1\""));
+        assert_eq!(remote.refresh(&mut filter).unwrap(), Refresh::NotModified);
+
+        server.publish("\"v2\"", "赌博\r\n\n博彩\n");
+        assert_eq!(remote.refresh(&mut filter).unwrap(), Refresh::Updated { words: 2 });
+        assert_eq!(filter.find_all("赌博 色情 博彩 代开发票"), ["代开发票", "博彩", "赌博"]);
+        assert_eq!(filter.layer_of("博彩"), Some("remote"));
+        let seen = server.seen.lock().unwrap().clone();
+        assert_eq!(seen, [None, Some("\"v1\"".into()), Some("\"v1\"".into())]);
+    }
+
+    #[test]
+    fn test_failed_refresh_keeps_filter() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dict.txt", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let _ = BufReader::new(&stream).read_line(&mut String::new());
+                let _ = stream.write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n");
+            }
+        });
+        let mut filter = Filter::new();
+        filter.add_layer("network", &["赌博"]);
+
+        let error = RemoteDict::new(url).refresh(&mut filter).unwrap_err();
+        assert!(error.to_string().contains("500"), "{error}");
+        assert!(filter.find_in("赌博").0);
+    }
+
+    #[test]
+    fn test_background_refresher_publishes_updates() {
+        let server = StandIn::start("\"v1\"", "赌博\n");
+        let shared = Arc::new(SharedFilter::new(Filter::new()));
+        let refresher = RemoteDict::new(&server.url).spawn(
+            Arc::clone(&shared),
+            Duration::from_millis(20),
+            Duration::from_millis(10),
+        );
+
+        let wait_for = |word: &str| {
+            let deadline = std::time::Instant::now() + Duration::from_secs(10);
+            while !shared.load().find_in(word).0 {
+                assert!(std::time::Instant::now() < deadline, "{word} never published");
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+        wait_for("赌博");
+        server.publish("\"v2\"", "色情\n");
+        wait_for("色情");
+        assert!(!shared.load().find_in("赌博").0);
+        assert!(refresher.take_error().is_none());
+        assert!(refresher.refreshes() >= 2);
+        refresher.stop();
+    }
+}
@@ -0,0 +1,160 @@
+//! Replacement strategies.
+//!
+//! [`ReplaceStrategy`] decides the text that stands in for each match in
+//! [`Filter::replace_with_strategy`](crate::Filter::replace_with_strategy) and
+//! [`MultiPatternEngine::replace_with_strategy`](crate::MultiPatternEngine::replace_with_strategy).
+//! Spans are resolved by the [`MatchKind`](crate::MatchKind) in effect before any strategy
+//! runs, so a strategy produces the same output whichever
+//! [`MatchAlgorithm`](crate::MatchAlgorithm) is in use.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::ops::Range;
+
+/// How a matched word is rewritten.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, ReplaceStrategy};
+///
+/// let mut filter = Filter::new();
+/// filter.add_words(&["赌博", "赌博机"]);
+///
+/// let text = "赌博和赌博机";
+/// assert_eq!(filter.replace_with_strategy(text, &ReplaceStrategy::Mask('*')), "**和***");
+/// assert_eq!(filter.replace_with_strategy(text, &ReplaceStrategy::Token("[x]".into())), "[x]和[x]");
+/// assert_eq!(filter.replace_with_strategy(text, &ReplaceStrategy::KeepEnds('*')), "赌*和赌*机");
+/// assert_eq!(
+///     filter.replace_with_strategy(text, &ReplaceStrategy::FixedLength { mask: '*', len: 4 }),
+///     "****和****"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceStrategy {
+    /// One `char` per matched character: `赌博` → `**`. This is what [`Filter::replace`](crate::Filter::replace) does.
+    Mask(char),
+    /// One fixed token per match, whatever its length: `赌博机` → `[censored]`.
+    Token(String),
+    /// Keep the first and last character and mask the middle: `赌博机` → `赌*机`.
+    ///
+    /// Two-character words keep only the first character (`赌博` → `赌*`) and single
+    /// characters are masked entirely, so the word is never shown in full.
+    KeepEnds(char),
+    /// `len` copies of `mask` per match, so the output does not reveal the word's length.
+    FixedLength {
+        /// Mask character.
+        mask: char,
+        /// Number of mask characters emitted for every match.
+        len: usize,
+    },
+    /// Replacement text chosen by the word's category (see
+    /// [`Filter::add_words_with_category`](crate::Filter::add_words_with_category)).
+    ///
+    /// Words without a category, or whose category has no entry in `replacements`, are
+    /// rewritten with `fallback`.
+    Category {
+        /// Category name → replacement text.
+        replacements: BTreeMap<String, String>,
+        /// Strategy for uncategorized words.
+        fallback: Box<ReplaceStrategy>,
+    },
+}
+
+impl Default for ReplaceStrategy {
+    fn default() -> Self {
+        Self::Mask('*')
+    }
+}
+
+impl ReplaceStrategy {
+    /// The replacement for one occurrence of `word`, given its category (if any).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::ReplaceStrategy;
+    ///
+    /// assert_eq!(ReplaceStrategy::Mask('#').replacement("赌博", None), "##");
+    /// assert_eq!(ReplaceStrategy::KeepEnds('*').replacement("abcd", None), "a**d");
+    /// ```
+    pub fn replacement<'a>(&'a self, word: &str, category: Option<&str>) -> Cow<'a, str> {
+        match self {
+            Self::Mask(mask) => Cow::Owned(repeat_char(*mask, word.chars().count())),
+            Self::Token(token) => Cow::Borrowed(token),
+            Self::KeepEnds(mask) => {
+                let mut chars = word.chars();
+                let count = word.chars().count();
+                let mut out = String::with_capacity(word.len());
+                match count {
+                    0 => {}
+                    1 => out.push(*mask),
+                    2 => {
+                        out.extend(chars.next());
+                        out.push(*mask);
+                    }
+                    _ => {
+                        out.extend(chars.next());
+                        out.push_str(&repeat_char(*mask, count - 2));
+                        out.extend(chars.next_back());
+                    }
+                }
+                Cow::Owned(out)
+            }
+            Self::FixedLength { mask, len } => Cow::Owned(repeat_char(*mask, *len)),
+            Self::Category { replacements, fallback } => match category.and_then(|c| replacements.get(c)) {
+                Some(text) => Cow::Borrowed(text),
+                None => fallback.replacement(word, category),
+            },
+        }
+    }
+}
+
+fn repeat_char(c: char, n: usize) -> String {
+    core::iter::repeat_n(c, n).collect()
+}
+
+/// Rebuild `text` with every `(span, replacement)` pair applied. Spans must be sorted
+/// and non-overlapping. Shared by the engine and [`Filter`](crate::Filter).
+pub(crate) fn rebuild<'r>(text: &str, replacements: impl IntoIterator<Item = (Range<usize>, Cow<'r, str>)>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0usize;
+    for (span, replacement) in replacements {
+        out.push_str(&text[cursor..span.start]);
+        out.push_str(&replacement);
+        cursor = span.end;
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_mask_per_char() {
+        assert_eq!(ReplaceStrategy::Mask('*').replacement("赌博机", None), "***");
+    }
+
+    #[test]
+    fn test_keep_ends_short_words() {
+        let s = ReplaceStrategy::KeepEnds('*');
+        assert_eq!(s.replacement("赌", None), "*");
+        assert_eq!(s.replacement("赌博", None), "赌*");
+        assert_eq!(s.replacement("赌博机", None), "赌*机");
+    }
+
+    #[test]
+    fn test_category_falls_back() {
+        let mut replacements = BTreeMap::new();
+        replacements.insert("gambling".to_string(), "[赌]".to_string());
+        let s = ReplaceStrategy::Category { replacements, fallback: Box::new(ReplaceStrategy::Mask('#')) };
+        assert_eq!(s.replacement("赌博", Some("gambling")), "[赌]");
+        assert_eq!(s.replacement("色情", Some("porn")), "##");
+        assert_eq!(s.replacement("色情", None), "##");
+    }
+}
@@ -0,0 +1,151 @@
+//! Lock-free sharing of a [`Filter`] whose dictionary changes while it is in use.
+//!
+//! [`SharedFilter`] publishes immutable dictionary snapshots. Readers take the current
+//! snapshot without locking; writers build the next one off to the side (engine, variant
+//! maps and a result cache of its own) and swap it in atomically, so a long rebuild never stalls
+//! matching and no reader sees a half-updated dictionary.
+
+use crate::filter::Filter;
+use alloc::sync::Arc;
+use arc_swap::ArcSwap;
+use std::sync::Mutex;
+
+/// A [`Filter`] shared between threads and updated by atomically swapping snapshots.
+///
+/// [`load`](SharedFilter::load) returns the current snapshot as an `Arc<Filter>`: a reader
+/// keeps matching against that snapshot for as long as it holds it, even while an update is
+/// published. [`update`](SharedFilter::update) copies the current snapshot, applies the change
+/// to the copy and publishes it. Updates are serialized, so concurrent updates are never lost.
+///
+/// A snapshot whose dictionary or noise pattern changed starts with its own result cache, so
+/// results cached for the old dictionary are never served for the new one.
+///
+/// # Examples
+///
+/// ```
+/// use sensitive_rs::{Filter, SharedFilter};
+/// use std::sync::Arc;
+///
+/// let shared = Arc::new(SharedFilter::new(Filter::new()));
+///
+/// let reader = {
+///     let shared = Arc::clone(&shared);
+///     std::thread::spawn(move || shared.load().find_all("赌博").len() <= 1)
+/// };
+/// shared.update(|filter| filter.add_words(&["赌博", "色情"]));
+///
+/// assert!(reader.join().unwrap());
+/// assert_eq!(shared.load().find_all("赌博和色情").len(), 2);
+/// ```
+pub struct SharedFilter {
+    current: ArcSwap<Filter>,
+    /// Serializes writers; readers never take it.
+    writer: Mutex<()>,
+}
+
+impl SharedFilter {
+    /// Share `filter` as the first snapshot.
+    pub fn new(filter: Filter) -> Self {
+        Self { current: ArcSwap::from_pointee(filter), writer: Mutex::new(()) }
+    }
+
+    /// The current snapshot. Lock-free; the snapshot stays valid and unchanged while held.
+    pub fn load(&self) -> Arc<Filter> {
+        self.current.load_full()
+    }
+
+    /// Build the next snapshot by applying `change` to a copy of the current one, then
+    /// publish it. Readers keep using the current snapshot until `change` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::{Filter, SharedFilter};
+    ///
+    /// let shared = SharedFilter::new(Filter::new());
+    /// let before = shared.load();
+    /// shared.update(|filter| filter.add_word("赌博"));
+    ///
+    /// assert!(!before.find_in("赌博").0);
+    /// assert!(shared.load().find_in("赌博").0);
+    /// ```
+    pub fn update<T>(&self, change: impl FnOnce(&mut Filter) -> T) -> T {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next = Filter::clone(&self.current.load());
+        let result = change(&mut next);
+        self.current.store(Arc::new(next));
+        result
+    }
+
+    /// Publish `filter` as the next snapshot, returning the one it replaces.
+    pub fn replace(&self, filter: Filter) -> Arc<Filter> {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        self.current.swap(Arc::new(filter))
+    }
+}
+
+impl From<Filter> for SharedFilter {
+    fn from(filter: Filter) -> Self {
+        Self::new(filter)
+    }
+}
+
+impl core::fmt::Debug for SharedFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SharedFilter").field("current", &self.current.load()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_survives_update() {
+        let shared = SharedFilter::new(Filter::new());
+        shared.update(|filter| filter.add_word("赌博"));
+        let old = shared.load();
+        assert_eq!(old.find_all("赌博色情"), ["赌博"]);
+
+        shared.update(|filter| {
+            filter.del_word("赌博");
+            filter.add_word("色情");
+        });
+        // The cached result of the old snapshot is not served by the new one.
+        assert_eq!(shared.load().find_all("赌博色情"), ["色情"]);
+        assert_eq!(old.find_all("赌博色情"), ["赌博"]);
+    }
+
+    #[test]
+    fn test_readers_see_whole_snapshots() {
+        let shared = Arc::new(SharedFilter::new(Filter::new()));
+        let words: Vec<String> = (0..200).map(|i| format!("词{i:03}")).collect();
+        let text = words.concat();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (shared, text) = (Arc::clone(&shared), text.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        // Each update adds a batch of 20 words: never a partial batch.
+                        assert_eq!(shared.load().find_all(&text).len() % 20, 0);
+                    }
+                })
+            })
+            .collect();
+        let writers: Vec<_> = words
+            .chunks(20)
+            .map(|batch| {
+                let (shared, batch) = (Arc::clone(&shared), batch.to_vec());
+                std::thread::spawn(move || {
+                    shared.update(|filter| filter.add_words(&batch.iter().map(String::as_str).collect::<Vec<_>>()))
+                })
+            })
+            .collect();
+        for handle in readers.into_iter().chain(writers) {
+            handle.join().unwrap();
+        }
+        // No update was lost.
+        assert_eq!(shared.load().find_all(&text).len(), 200);
+    }
+}
@@ -0,0 +1,414 @@
+//! Versioned binary snapshots of a built [`Filter`](crate::Filter).
+//!
+//! A snapshot stores everything [`Filter::load_snapshot`](crate::Filter::load_snapshot) needs to bring a filter back
+//! without repeating the per-word work of building it: the expanded pattern list, priorities
+//! and categories, the Wu-Manber shift/hash tables, the pinyin index, and the noise, matching
+//! and replacement settings. The Aho-Corasick and regex automata cannot be serialized and are
+//! compiled from the stored pattern list, which is the cheap part of a build.
+//!
+//! Layout, integers little-endian:
+//!
+//! | Bytes | Content |
+//! |-------|---------|
+//! | 6     | magic `SRSNAP` |
+//! | 2     | format version ([`SNAPSHOT_VERSION`]) |
+//! | 8     | payload length |
+//! | n     | payload |
+//! | 8     | FNV-1a 64 checksum of the payload |
+
+use crate::replace::ReplaceStrategy;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::io::{self, Read, Write};
+
+/// Version of the snapshot format written by [`Filter::save_snapshot`](crate::Filter::save_snapshot). Snapshots of any
+/// other version are rejected by [`Filter::load_snapshot`](crate::Filter::load_snapshot).
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 6] = b"SRSNAP";
+
+/// Deepest chain of [`ReplaceStrategy::Category`] fallbacks a snapshot may hold, so a crafted
+/// payload cannot recurse the decoder off the stack.
+pub(crate) const MAX_STRATEGY_DEPTH: usize = 32;
+
+/// Number of [`ReplaceStrategy::Category`] fallbacks `strategy` nests.
+pub(crate) fn strategy_depth(mut strategy: &ReplaceStrategy) -> usize {
+    let mut depth = 0;
+    while let ReplaceStrategy::Category { fallback, .. } = strategy {
+        depth += 1;
+        strategy = fallback;
+    }
+    depth
+}
+
+/// Why [`Filter::load_snapshot`](crate::Filter::load_snapshot) rejected its input.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// Reading the snapshot failed.
+    Io(io::Error),
+    /// The input does not start with the snapshot magic bytes.
+    NotASnapshot,
+    /// The snapshot was written in another format version.
+    UnsupportedVersion(u16),
+    /// The input ends before the length recorded in its header.
+    Truncated,
+    /// The payload does not match its checksum.
+    ChecksumMismatch,
+    /// The payload passed the checksum but does not decode to a valid filter.
+    Corrupt(&'static str),
+    /// The stored noise pattern does not compile.
+    NoisePattern(regex::Error),
+}
+
+impl core::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read snapshot: {e}"),
+            Self::NotASnapshot => f.write_str("not a filter snapshot"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {v} (expected {SNAPSHOT_VERSION})")
+            }
+            Self::Truncated => f.write_str("snapshot is truncated"),
+            Self::ChecksumMismatch => f.write_str("snapshot checksum mismatch"),
+            Self::Corrupt(what) => write!(f, "corrupt snapshot: {what}"),
+            Self::NoisePattern(e) => write!(f, "invalid noise pattern in snapshot: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::NoisePattern(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof { Self::Truncated } else { Self::Io(e) }
+    }
+}
+
+/// Frame `payload` with the header and checksum.
+pub(crate) fn write(mut writer: impl Write, payload: &[u8]) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&fnv1a(payload).to_le_bytes())?;
+    writer.flush()
+}
+
+/// Read a framed payload, checking the header and checksum.
+pub(crate) fn read(mut reader: impl Read) -> Result<Vec<u8>, SnapshotError> {
+    let mut magic = [0; 6];
+    reader.read_exact(&mut magic).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => SnapshotError::NotASnapshot,
+        _ => SnapshotError::Io(e),
+    })?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::NotASnapshot);
+    }
+    let mut version = [0; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+
+    // Read through `take` rather than allocating `len` up front: a corrupt length must not
+    // turn into a huge allocation.
+    let mut payload = Vec::new();
+    reader.by_ref().take(len).read_to_end(&mut payload)?;
+    if payload.len() as u64 != len {
+        return Err(SnapshotError::Truncated);
+    }
+    let mut checksum = [0; 8];
+    reader.read_exact(&mut checksum)?;
+    if u64::from_le_bytes(checksum) != fnv1a(&payload) {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+    Ok(payload)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x100_0000_01b3))
+}
+
+/// Appends snapshot fields to a payload.
+#[derive(Default)]
+pub(crate) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub(crate) fn bool(&mut self, v: bool) {
+        self.u8(u8::from(v));
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn usize(&mut self, v: usize) {
+        self.u64(v as u64);
+    }
+
+    pub(crate) fn i32(&mut self, v: i32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn char(&mut self, v: char) {
+        self.buf.extend_from_slice(&u32::from(v).to_le_bytes());
+    }
+
+    pub(crate) fn str(&mut self, v: &str) {
+        self.usize(v.len());
+        self.buf.extend_from_slice(v.as_bytes());
+    }
+
+    pub(crate) fn strs<S: AsRef<str>>(&mut self, items: &[S]) {
+        self.usize(items.len());
+        for item in items {
+            self.str(item.as_ref());
+        }
+    }
+}
+
+/// Reads snapshot fields back from a payload, rejecting anything out of range.
+pub(crate) struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Check that the whole payload was consumed.
+    pub(crate) fn finish(self) -> Result<(), SnapshotError> {
+        if self.data.is_empty() { Ok(()) } else { Err(SnapshotError::Corrupt("trailing bytes")) }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if n > self.data.len() {
+            return Err(SnapshotError::Corrupt("field runs past the end of the payload"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Corrupt("invalid boolean")),
+        }
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize, SnapshotError> {
+        usize::try_from(self.u64()?).map_err(|_| SnapshotError::Corrupt("value out of range"))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, SnapshotError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    pub(crate) fn char(&mut self) -> Result<char, SnapshotError> {
+        let v = u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes"));
+        char::from_u32(v).ok_or(SnapshotError::Corrupt("invalid character"))
+    }
+
+    /// A collection length. Every element takes at least one byte, so a length beyond the
+    /// remaining payload is corrupt; checking it keeps allocations bounded by the input.
+    pub(crate) fn len(&mut self) -> Result<usize, SnapshotError> {
+        let len = self.usize()?;
+        if len > self.data.len() {
+            return Err(SnapshotError::Corrupt("collection longer than the payload"));
+        }
+        Ok(len)
+    }
+
+    pub(crate) fn str(&mut self) -> Result<&'a str, SnapshotError> {
+        let len = self.len()?;
+        core::str::from_utf8(self.take(len)?).map_err(|_| SnapshotError::Corrupt("invalid UTF-8"))
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String, SnapshotError> {
+        self.str().map(String::from)
+    }
+
+    pub(crate) fn strings(&mut self) -> Result<Vec<String>, SnapshotError> {
+        (0..self.len()?).map(|_| self.string()).collect()
+    }
+}
+
+pub(crate) fn encode_strategy(e: &mut Encoder, strategy: &ReplaceStrategy) {
+    match strategy {
+        ReplaceStrategy::Mask(mask) => {
+            e.u8(0);
+            e.char(*mask);
+        }
+        ReplaceStrategy::Token(token) => {
+            e.u8(1);
+            e.str(token);
+        }
+        ReplaceStrategy::KeepEnds(mask) => {
+            e.u8(2);
+            e.char(*mask);
+        }
+        ReplaceStrategy::FixedLength { mask, len } => {
+            e.u8(3);
+            e.char(*mask);
+            e.usize(*len);
+        }
+        ReplaceStrategy::Category { replacements, fallback } => {
+            e.u8(4);
+            e.usize(replacements.len());
+            for (category, replacement) in replacements {
+                e.str(category);
+                e.str(replacement);
+            }
+            encode_strategy(e, fallback);
+        }
+    }
+}
+
+pub(crate) fn decode_strategy(d: &mut Decoder<'_>) -> Result<ReplaceStrategy, SnapshotError> {
+    decode_nested_strategy(d, 0)
+}
+
+fn decode_nested_strategy(d: &mut Decoder<'_>, depth: usize) -> Result<ReplaceStrategy, SnapshotError> {
+    if depth > MAX_STRATEGY_DEPTH {
+        return Err(SnapshotError::Corrupt("replacement strategy nested too deeply"));
+    }
+    Ok(match d.u8()? {
+        0 => ReplaceStrategy::Mask(d.char()?),
+        1 => ReplaceStrategy::Token(d.string()?),
+        2 => ReplaceStrategy::KeepEnds(d.char()?),
+        3 => ReplaceStrategy::FixedLength { mask: d.char()?, len: d.usize()? },
+        4 => {
+            let replacements: BTreeMap<String, String> =
+                (0..d.len()?).map(|_| Ok((d.string()?, d.string()?))).collect::<Result<_, SnapshotError>>()?;
+            ReplaceStrategy::Category { replacements, fallback: Box::new(decode_nested_strategy(d, depth + 1)?) }
+        }
+        _ => return Err(SnapshotError::Corrupt("unknown replacement strategy")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DictEntry, Filter, MatchAlgorithm};
+
+    fn snapshot(filter: &Filter) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        filter.save_snapshot(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip_matches_original() {
+        let text = "赌@博 dubo 睹博 赌博机 色 情 代开发票";
+        for algorithm in [MatchAlgorithm::WuManber, MatchAlgorithm::AhoCorasick, MatchAlgorithm::Regex] {
+            let mut filter = Filter::with_algorithm(algorithm);
+            filter.add_words(&["赌博", "赌博机", "色 情"]);
+            filter.add_words_with_category(&["代开发票"], "fraud");
+            let mut entry = DictEntry::new("赌博机");
+            (entry.severity, entry.replacement) = (Some(3), Some("[机]".into()));
+            filter.add_entries(&[entry]);
+            filter.add_layer("event", &["赌博", "睹博"]);
+            filter.set_layer_enabled("event", false);
+            filter.set_replace_strategy(ReplaceStrategy::Category {
+                replacements: [("fraud".into(), "[F]".into())].into(),
+                fallback: Box::new(ReplaceStrategy::KeepEnds('#')),
+            });
+
+            let loaded = Filter::load_snapshot(snapshot(&filter).as_slice()).unwrap();
+            assert_eq!(loaded.current_algorithm(), algorithm);
+            assert_eq!(loaded.find_all(text), filter.find_all(text));
+            assert_eq!(loaded.find_occurrences(text), filter.find_occurrences(text));
+            assert_eq!(loaded.redact(text), filter.redact(text));
+            assert_eq!(loaded.replace_strategy(), filter.replace_strategy());
+            assert_eq!(loaded.severity_of("赌博机"), Some(3));
+            assert_eq!((loaded.layer_words("event").unwrap().len(), loaded.is_layer_enabled("event")), (2, false));
+            // Saving the loaded filter gives the same bytes.
+            assert_eq!(snapshot(&loaded), snapshot(&filter));
+        }
+    }
+
+    #[test]
+    fn test_damaged_snapshots_are_rejected() {
+        let mut filter = Filter::new();
+        filter.add_words(&["赌博", "色情"]);
+        let bytes = snapshot(&filter);
+
+        assert!(matches!(Filter::load_snapshot(&b"not a snapshot"[..]), Err(SnapshotError::NotASnapshot)));
+        assert!(matches!(Filter::load_snapshot(&bytes[..bytes.len() - 3]), Err(SnapshotError::Truncated)));
+
+        let mut flipped = bytes.clone();
+        flipped[40] ^= 1;
+        assert!(matches!(Filter::load_snapshot(flipped.as_slice()), Err(SnapshotError::ChecksumMismatch)));
+
+        let mut future = bytes.clone();
+        future[6..8].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        assert!(matches!(Filter::load_snapshot(future.as_slice()), Err(SnapshotError::UnsupportedVersion(_))));
+
+        // A well-formed frame around a payload that does not decode.
+        let mut framed = Vec::new();
+        write(&mut framed, &[0xff; 16]).unwrap();
+        assert!(matches!(Filter::load_snapshot(framed.as_slice()), Err(SnapshotError::Corrupt(_))));
+    }
+
+    #[test]
+    fn test_nested_strategies_are_bounded() {
+        let nest = |depth: usize| {
+            (0..depth).fold(ReplaceStrategy::Mask('*'), |fallback, _| ReplaceStrategy::Category {
+                replacements: BTreeMap::new(),
+                fallback: Box::new(fallback),
+            })
+        };
+        let encoded = |strategy: &ReplaceStrategy| {
+            let mut e = Encoder::default();
+            encode_strategy(&mut e, strategy);
+            e.into_bytes()
+        };
+
+        let fine = nest(MAX_STRATEGY_DEPTH);
+        assert_eq!(decode_strategy(&mut Decoder::new(&encoded(&fine))).unwrap(), fine);
+        let mut filter = Filter::new();
+        filter.set_replace_strategy(nest(MAX_STRATEGY_DEPTH + 1));
+        let error = filter.save_snapshot(Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        // Each level is 9 bytes, so a payload of a few megabytes could nest far enough to
+        // overflow the stack; decoding stops at the limit instead.
+        let deep = [4u8, 0, 0, 0, 0, 0, 0, 0, 0].repeat(1_000_000);
+        let error = decode_strategy(&mut Decoder::new(&deep)).unwrap_err();
+        assert!(matches!(error, SnapshotError::Corrupt("replacement strategy nested too deeply")));
+    }
+}
@@ -0,0 +1,820 @@
+//! Streaming scan of large inputs.
+//!
+//! [`Filter::scan_reader`](crate::Filter::scan_reader) reads any [`Read`] in fixed-size chunks
+//! and yields [`StreamMatch`]es with absolute byte offsets and line/column positions. A word
+//! split across two chunks is still found: the tail of each chunk that could hold the start of
+//! an unfinished match is carried into the next one, so memory stays bounded by the chunk size
+//! plus the longest dictionary word, whatever the size of the input.
+//!
+//! [`Scanner`] applies the same boundary handling to text pushed in fragments, such as chat
+//! messages or streamed model output.
+
+use crate::filter::{Filter, Match};
+use core::ops::Range;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+/// Default number of bytes read per chunk by [`StreamScanner`].
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A match found by a streaming scan, positioned in the whole input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamMatch {
+    /// The matched dictionary word.
+    pub word: String,
+    /// Absolute byte offset where the match starts.
+    pub start: u64,
+    /// Absolute byte offset where the match ends (exclusive).
+    pub end: u64,
+    /// Line of `start`, counting from 1. Lines are separated by `\n`.
+    pub line: u64,
+    /// Column of `start` within its line, in characters, counting from 1.
+    pub column: u64,
+}
+
+/// Line/column bookkeeping for an absolute byte offset.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    offset: u64,
+    line: u64,
+    column: u64,
+}
+
+impl Position {
+    /// Advance over `text`, which must start at `self.offset`.
+    fn advance(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset += text.len() as u64;
+    }
+}
+
+/// Chunk-boundary handling shared by the streaming scanners.
+///
+/// Text is fed in pieces; each call reports the matches that can no longer change and keeps
+/// the unfinished tail, so the result is the same as scanning the concatenated input at once.
+/// Noise inside the tail is set aside, so a long run of it after the start of a word is not
+/// scanned again on every feed.
+#[derive(Debug)]
+pub(crate) struct ChunkCore {
+    /// Input not yet released, without the noise in `gaps`. Between feeds its first byte
+    /// is at `pos`.
+    carry: String,
+    pos: Position,
+    /// Noise taken out of `carry`, by offset.
+    gaps: Vec<Gap>,
+}
+
+/// Noise taken out of the carry.
+#[derive(Debug)]
+struct Gap {
+    /// Offset in the carry where the noise was.
+    at: usize,
+    /// Where the input continues after it.
+    after: Position,
+    /// The noise itself, kept only while the released input is collected.
+    noise: String,
+}
+
+impl ChunkCore {
+    pub(crate) fn new() -> Self {
+        Self { carry: String::new(), pos: Position { offset: 0, line: 1, column: 1 }, gaps: Vec::new() }
+    }
+
+    /// Absolute offset of the first byte not yet released.
+    pub(crate) fn released(&self) -> u64 {
+        self.pos.offset
+    }
+
+    /// Absolute offset just past everything fed so far.
+    pub(crate) fn fed(&self) -> u64 {
+        self.offset(self.carry.len(), true)
+    }
+
+    /// Bytes held back for matching, not counting noise set aside.
+    pub(crate) fn pending(&self) -> usize {
+        self.carry.len()
+    }
+
+    /// Absolute offset of carry offset `at`, after the noise taken out there with `past_gap`.
+    fn offset(&self, at: usize, past_gap: bool) -> u64 {
+        match self.gaps.partition_point(|gap| gap.at < at || (past_gap && gap.at == at)) {
+            0 => self.pos.offset + at as u64,
+            i => self.gaps[i - 1].after.offset + (at - self.gaps[i - 1].at) as u64,
+        }
+    }
+
+    /// Move `cursor`, a carry offset, the index of the next gap and its position, forward to
+    /// carry offset `to`, past the noise taken out there.
+    fn seek(&self, cursor: &mut (usize, usize, Position), to: usize) -> Position {
+        let (at, gap, pos) = cursor;
+        while let Some(next) = self.gaps.get(*gap).filter(|next| next.at <= to) {
+            pos.advance(&self.carry[*at..next.at]);
+            (*at, *pos) = (next.at, next.after);
+            *gap += 1;
+        }
+        pos.advance(&self.carry[*at..to]);
+        *at = to;
+        *pos
+    }
+
+    /// Append `text` and push every match that is now final to `out`. With `last`, the
+    /// input is complete and everything left is final. The input released by this call is
+    /// appended to `released` when given.
+    pub(crate) fn feed(
+        &mut self,
+        filter: &Filter,
+        text: &str,
+        last: bool,
+        out: &mut VecDeque<StreamMatch>,
+        released: Option<&mut String>,
+    ) {
+        self.carry.push_str(text);
+        let clean = filter.clean(&self.carry);
+        let clean_len = clean.text.len();
+
+        // A match starting at or after `safe` may continue past the end of what we have.
+        let safe = if last { clean_len } else { clean_len - filter.engine().unfinished_suffix(&clean.text) };
+
+        let mut resume = safe;
+        let mut cursor = (0, 0, self.pos);
+        for m in filter.exact_matches(&clean.text, false) {
+            if m.start >= safe {
+                break;
+            }
+            resume = resume.max(m.end);
+            let (start, end) = clean.input_span(m.start, m.end);
+            let at = self.seek(&mut cursor, start);
+            out.push_back(StreamMatch {
+                word: m.pattern,
+                start: at.offset,
+                end: self.offset(end, false),
+                line: at.line,
+                column: at.column,
+            });
+        }
+
+        // Release everything before `resume`; trailing noise is never part of a match.
+        let cut = if resume >= clean_len { self.carry.len() } else { clean.input_span(resume, resume).0 };
+        let runs = clean.input_runs();
+        drop(clean);
+        let pos = self.seek(&mut cursor, cut);
+        let keep = released.is_some();
+        if let Some(released) = released {
+            let mut at = 0;
+            for gap in &self.gaps[..cursor.1] {
+                released.push_str(&self.carry[at..gap.at]);
+                released.push_str(&gap.noise);
+                at = gap.at;
+            }
+            released.push_str(&self.carry[at..cut]);
+        }
+        self.compact(cut, &runs, pos, cursor.1, keep);
+    }
+
+    /// Replace the carry with its part from `cut`, which is at `pos`, keeping only the
+    /// input `runs` the clean text is made of and setting the rest aside as gaps.
+    fn compact(&mut self, cut: usize, runs: &[Range<usize>], mut pos: Position, first_gap: usize, keep: bool) {
+        let mut old = core::mem::take(&mut self.gaps).into_iter().skip(first_gap).peekable();
+        let mut carry = String::with_capacity(self.carry.len() - cut);
+        let mut at = cut;
+        self.pos = pos;
+        let kept = runs.iter().filter(|run| run.end > cut).map(|run| run.start.max(cut)..run.end);
+        for run in kept.chain(core::iter::once(self.carry.len()..self.carry.len())) {
+            // Noise before the run, merged with any already set aside there.
+            let mut noise = String::new();
+            let mut removed = false;
+            while let Some(gap) = old.next_if(|gap| gap.at <= run.start) {
+                pos.advance(&self.carry[at..gap.at]);
+                if keep {
+                    noise.push_str(&self.carry[at..gap.at]);
+                    if noise.is_empty() {
+                        noise = gap.noise;
+                    } else {
+                        noise.push_str(&gap.noise);
+                    }
+                }
+                (at, pos, removed) = (gap.at, gap.after, true);
+            }
+            if run.start > at {
+                pos.advance(&self.carry[at..run.start]);
+                if keep {
+                    noise.push_str(&self.carry[at..run.start]);
+                }
+                (at, removed) = (run.start, true);
+            }
+            if removed {
+                self.gaps.push(Gap { at: carry.len(), after: pos, noise });
+            }
+            // The run itself, split where noise was set aside before.
+            while let Some(gap) = old.next_if(|gap| gap.at < run.end) {
+                carry.push_str(&self.carry[at..gap.at]);
+                pos.advance(&self.carry[at..gap.at]);
+                self.gaps.push(Gap { at: carry.len(), after: gap.after, noise: gap.noise });
+                (at, pos) = (gap.at, gap.after);
+            }
+            carry.push_str(&self.carry[at..run.end]);
+            pos.advance(&self.carry[at..run.end]);
+            at = run.end;
+        }
+        self.carry = carry;
+    }
+}
+
+/// UTF-8 decoding in front of a [`ChunkCore`], for sources that deliver raw bytes.
+#[derive(Debug)]
+pub(crate) struct ByteFeeder {
+    core: ChunkCore,
+    /// Trailing bytes of an incomplete UTF-8 sequence, completed by the next push.
+    partial: Vec<u8>,
+}
+
+impl ByteFeeder {
+    pub(crate) fn new() -> Self {
+        Self { core: ChunkCore::new(), partial: Vec::new() }
+    }
+
+    /// Feed the next bytes of the input. Invalid UTF-8 ends the input: the text before it
+    /// is scanned to the end and the error is returned.
+    pub(crate) fn push(&mut self, filter: &Filter, bytes: &[u8], out: &mut VecDeque<StreamMatch>) -> io::Result<()> {
+        self.partial.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text.len(),
+            // Cut mid-character: keep the incomplete sequence for the next push.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                let error = self.invalid_utf8(e.valid_up_to());
+                self.finish(filter, e.valid_up_to(), out);
+                return Err(error);
+            }
+        };
+        let text = std::str::from_utf8(&self.partial[..valid]).expect("validated above");
+        self.core.feed(filter, text, false, out, None);
+        self.partial.drain(..valid);
+        Ok(())
+    }
+
+    /// The input is complete: release every remaining match. Fails if the input ends
+    /// inside a UTF-8 sequence.
+    pub(crate) fn end(&mut self, filter: &Filter, out: &mut VecDeque<StreamMatch>) -> io::Result<()> {
+        let error = (!self.partial.is_empty()).then(|| self.invalid_utf8(0));
+        self.finish(filter, 0, out);
+        error.map_or(Ok(()), Err)
+    }
+
+    /// The source failed: treat what was delivered so far as the whole input.
+    pub(crate) fn abort(&mut self, filter: &Filter, out: &mut VecDeque<StreamMatch>) {
+        self.finish(filter, 0, out);
+    }
+
+    /// Scan the first `valid` pending bytes as the end of the input.
+    fn finish(&mut self, filter: &Filter, valid: usize, out: &mut VecDeque<StreamMatch>) {
+        let text = std::str::from_utf8(&self.partial[..valid]).expect("validated by caller");
+        self.core.feed(filter, text, true, out, None);
+        self.partial.clear();
+    }
+
+    fn invalid_utf8(&self, valid_up_to: usize) -> io::Error {
+        let offset = self.core.fed() + valid_up_to as u64;
+        io::Error::new(io::ErrorKind::InvalidData, format!("stream is not valid UTF-8 near byte {offset}"))
+    }
+}
+
+/// Iterator over the matches of a [`Read`], created by
+/// [`Filter::scan_reader`](crate::Filter::scan_reader).
+///
+/// Reads lazily, one chunk at a time, as matches are requested. An I/O error or invalid UTF-8
+/// is reported once as `Err`, after the matches in the input before it, and ends the iterator.
+pub struct StreamScanner<'f, R> {
+    filter: &'f Filter,
+    reader: R,
+    feeder: ByteFeeder,
+    chunk: Vec<u8>,
+    queue: VecDeque<StreamMatch>,
+    error: Option<io::Error>,
+    done: bool,
+}
+
+impl<'f, R: Read> StreamScanner<'f, R> {
+    pub(crate) fn new(filter: &'f Filter, reader: R) -> Self {
+        Self {
+            filter,
+            reader,
+            feeder: ByteFeeder::new(),
+            chunk: vec![0; DEFAULT_CHUNK_SIZE],
+            queue: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Read `size` bytes per chunk instead of 64 KiB (at least 4).
+    #[must_use]
+    pub fn with_chunk_size(mut self, size: usize) -> Self {
+        self.chunk = vec![0; size.max(4)];
+        self
+    }
+
+    /// Read the next chunk and feed it. Errors end the input: what was read before them
+    /// is scanned to the end and the error is kept for the caller.
+    fn fill(&mut self) {
+        let result = loop {
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    break self.feeder.end(self.filter, &mut self.queue);
+                }
+                Ok(n) => break self.feeder.push(self.filter, &self.chunk[..n], &mut self.queue),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.feeder.abort(self.filter, &mut self.queue);
+                    break Err(e);
+                }
+            }
+        };
+        if let Err(e) = result {
+            self.error = Some(e);
+            self.done = true;
+        }
+    }
+}
+
+impl<R: Read> Iterator for StreamScanner<'_, R> {
+    type Item = io::Result<StreamMatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(m) = self.queue.pop_front() {
+                return Some(Ok(m));
+            }
+            if let Some(e) = self.error.take() {
+                return Some(Err(e));
+            }
+            if self.done {
+                return None;
+            }
+            self.fill();
+        }
+    }
+}
+
+impl<R> core::fmt::Debug for StreamScanner<'_, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StreamScanner")
+            .field("chunk_size", &self.chunk.len())
+            .field("released", &self.feeder.core.released())
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Incremental scanner for text that arrives in fragments, created by
+/// [`Filter::scanner`](crate::Filter::scanner).
+///
+/// Each [`push`](Scanner::push) reports the matches that are now certain, even when a word is
+/// split across fragments ("赌" then "博"); [`finish`](Scanner::finish) reports the rest once
+/// the input is complete. Only the tail that could still be the start of a match is rescanned,
+/// at most the length of the longest dictionary word; noise after it is set aside until it is
+/// released. Match offsets count from the start of
+/// the input. Like [`Filter::scan_reader`](crate::Filter::scan_reader), only exact dictionary
+/// matches are reported.
+///
+/// For redact-before-display pipelines, [`release`](Scanner::release) hands out the prefix of
+/// the input that can no longer change, with its matches masked.
+#[derive(Debug)]
+pub struct Scanner<'f> {
+    filter: &'f Filter,
+    core: ChunkCore,
+    /// Released input not yet taken by [`Scanner::release`].
+    released: String,
+    /// Spans of reported matches within `released`.
+    masked: Vec<(usize, usize)>,
+    queue: VecDeque<StreamMatch>,
+}
+
+impl<'f> Scanner<'f> {
+    pub(crate) fn new(filter: &'f Filter) -> Self {
+        Self { filter, core: ChunkCore::new(), released: String::new(), masked: Vec::new(), queue: VecDeque::new() }
+    }
+
+    /// Append the next fragment and return the matches completed by it.
+    ///
+    /// A match is returned as soon as no later fragment could extend it into a longer one,
+    /// which for a word that is a prefix of another may be a push or two later.
+    pub fn push(&mut self, text: &str) -> Vec<Match> {
+        self.feed(text, false)
+    }
+
+    /// End the input and return the matches still pending. The scanner can then be reused
+    /// for a new input; offsets start from 0 again.
+    pub fn finish(&mut self) -> Vec<Match> {
+        let matches = self.feed("", true);
+        self.core = ChunkCore::new();
+        matches
+    }
+
+    /// Take the input that can no longer be part of an unreported match, with every match
+    /// in it replaced by `mask`, one per character. Concatenating the results of all calls
+    /// (after [`finish`](Scanner::finish)) gives the whole input, masked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::Filter;
+    ///
+    /// let mut filter = Filter::new();
+    /// filter.add_word("赌博");
+    ///
+    /// let mut scanner = filter.scanner();
+    /// scanner.push("今天去赌");
+    /// assert_eq!(scanner.release('*'), "今天去");
+    /// scanner.push("博了");
+    /// assert_eq!(scanner.release('*'), "**了");
+    /// ```
+    pub fn release(&mut self, mask: char) -> String {
+        let mut out = String::with_capacity(self.released.len());
+        let mut last = 0;
+        for &(start, end) in &self.masked {
+            out.push_str(&self.released[last..start]);
+            out.extend(self.released[start..end].chars().map(|_| mask));
+            last = end;
+        }
+        out.push_str(&self.released[last..]);
+        self.released.clear();
+        self.masked.clear();
+        out
+    }
+
+    /// Number of bytes of input held back because they could still begin a match. Noise
+    /// following them is set aside and not counted.
+    pub fn pending_len(&self) -> usize {
+        self.core.pending()
+    }
+
+    fn feed(&mut self, text: &str, last: bool) -> Vec<Match> {
+        // Matches reported now lie within the input released by this feed, which is
+        // appended to `released`.
+        let (from, offset) = (self.core.released() as usize, self.released.len());
+        self.core.feed(self.filter, text, last, &mut self.queue, Some(&mut self.released));
+        self.queue
+            .drain(..)
+            .map(|m| {
+                let (start, end) = (m.start as usize, m.end as usize);
+                self.masked.push((start - from + offset, end - from + offset));
+                let layer = self.filter.layer_of(&m.word).map(String::from);
+                Match { word: m.word, is_variant: false, start, end, layer }
+            })
+            .collect()
+    }
+}
+
+/// Matches of an async source, created by
+/// [`Filter::scan_async_read`](crate::Filter::scan_async_read) or
+/// [`Filter::scan_async_stream`](crate::Filter::scan_async_stream).
+///
+/// A task spawned on the current tokio runtime reads the source and hands each chunk to the
+/// blocking pool for matching, so the async executor never runs the scan itself. Results are
+/// the same as [`StreamScanner`]'s. At most a few chunks' worth of matches are buffered ahead
+/// of the consumer; dropping the stream stops the scan.
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct AsyncStreamScanner {
+    rx: tokio::sync::mpsc::Receiver<io::Result<VecDeque<StreamMatch>>>,
+    batch: VecDeque<StreamMatch>,
+}
+
+#[cfg(feature = "async-io")]
+impl AsyncStreamScanner {
+    /// Batches of matches buffered between the scanning task and the consumer.
+    const BUFFERED_BATCHES: usize = 4;
+
+    pub(crate) fn spawn<S, B, E>(filter: std::sync::Arc<Filter>, source: S) -> Self
+    where
+        S: futures_core::Stream<Item = Result<B, E>> + Send + Unpin + 'static,
+        B: AsRef<[u8]> + Send + 'static,
+        E: Into<io::Error> + Send + 'static,
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel(Self::BUFFERED_BATCHES);
+        tokio::spawn(Self::produce(filter, source, tx));
+        Self { rx, batch: VecDeque::new() }
+    }
+
+    async fn produce<S, B, E>(
+        filter: std::sync::Arc<Filter>,
+        mut source: S,
+        tx: tokio::sync::mpsc::Sender<io::Result<VecDeque<StreamMatch>>>,
+    ) where
+        S: futures_core::Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]> + Send + 'static,
+        E: Into<io::Error> + Send + 'static,
+    {
+        let mut feeder = ByteFeeder::new();
+        loop {
+            let item = std::future::poll_fn(|cx| core::pin::Pin::new(&mut source).poll_next(cx)).await;
+            let (chunk, source_error): (Option<B>, Option<io::Error>) = match item {
+                Some(Ok(chunk)) => (Some(chunk), None),
+                Some(Err(e)) => (None, Some(e.into())),
+                None => (None, None),
+            };
+            let failed = source_error.is_some();
+            let filter = filter.clone();
+            let scanned = tokio::task::spawn_blocking(move || {
+                let mut out = VecDeque::new();
+                let result = match &chunk {
+                    Some(chunk) => feeder.push(&filter, chunk.as_ref(), &mut out),
+                    None if failed => {
+                        feeder.abort(&filter, &mut out);
+                        Ok(())
+                    }
+                    None => feeder.end(&filter, &mut out),
+                };
+                (feeder, out, result, chunk.is_none())
+            })
+            .await;
+            let (returned, out, result, last) = match scanned {
+                Ok(scanned) => scanned,
+                Err(join) => {
+                    let _ = tx.send(Err(io::Error::other(join))).await;
+                    return;
+                }
+            };
+            feeder = returned;
+            if !out.is_empty() && tx.send(Ok(out)).await.is_err() {
+                return; // consumer dropped the stream
+            }
+            if let Some(e) = source_error.or(result.err()) {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+            if last {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl futures_core::Stream for AsyncStreamScanner {
+    type Item = io::Result<StreamMatch>;
+
+    fn poll_next(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<Self::Item>> {
+        use core::task::Poll;
+        loop {
+            if let Some(m) = self.batch.pop_front() {
+                return Poll::Ready(Some(Ok(m)));
+            }
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(Ok(batch))) => self.batch = batch,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Adapts an `AsyncRead` into a stream of chunks for [`AsyncStreamScanner`].
+#[cfg(feature = "async-io")]
+pub(crate) struct ReaderChunks<R> {
+    reader: R,
+    /// Read buffer, reused by every poll; each chunk is copied out at the size read.
+    buf: Vec<u8>,
+}
+
+#[cfg(feature = "async-io")]
+impl<R> ReaderChunks<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self { reader, buf: vec![0; DEFAULT_CHUNK_SIZE] }
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl<R: tokio::io::AsyncRead + Unpin> futures_core::Stream for ReaderChunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<Self::Item>> {
+        use core::task::Poll;
+        let this = &mut *self;
+        let mut buf = tokio::io::ReadBuf::new(&mut this.buf);
+        match core::pin::Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => Poll::Ready(None),
+            Poll::Ready(Ok(())) => Poll::Ready(Some(Ok(buf.filled().to_vec()))),
+            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_with(words: &[&str]) -> Filter {
+        let mut filter = Filter::new();
+        filter.add_words(words);
+        filter
+    }
+
+    fn scan(filter: &Filter, input: &str, chunk_size: usize) -> Vec<StreamMatch> {
+        filter.scan_reader(input.as_bytes()).with_chunk_size(chunk_size).collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn test_same_matches_at_every_chunk_size() {
+        let filter = filter_with(&["赌", "赌博", "赌博机", "色情", "abc"]);
+        let input = "赌博机\n含有色情 ab\nc abc 赌@博 色情色情\n赌";
+        let expected: Vec<(String, usize, usize)> = filter
+            .find_occurrences(input)
+            .into_iter()
+            .filter(|m| !m.is_variant)
+            .map(|m| (m.word, m.start, m.end))
+            .collect();
+        assert_eq!(expected.len(), 7);
+        for chunk_size in 4..=input.len() + 1 {
+            let found: Vec<(String, usize, usize)> = scan(&filter, input, chunk_size)
+                .into_iter()
+                .map(|m| (m.word, m.start as usize, m.end as usize))
+                .collect();
+            assert_eq!(found, expected, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_line_and_column() {
+        let filter = filter_with(&["赌博"]);
+        let found = scan(&filter, "赌博\nxx赌博\n\n  赌博", 5);
+        let positions: Vec<(u64, u64, u64)> = found.iter().map(|m| (m.start, m.line, m.column)).collect();
+        assert_eq!(positions, vec![(0, 1, 1), (9, 2, 3), (19, 4, 3)]);
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_an_error() {
+        let filter = filter_with(&["赌博"]);
+        let mut input = "赌博".as_bytes().to_vec();
+        input.extend_from_slice(&[0xff, b'a']);
+        let results: Vec<io::Result<StreamMatch>> = filter.scan_reader(input.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().word, "赌博");
+        assert_eq!(results[1].as_ref().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // A truncated multi-byte character at the end of the input.
+        let truncated = &"赌博".as_bytes()[..4];
+        let err = filter.scan_reader(truncated).next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_read_error_after_matches() {
+        struct Failing<'a>(&'a [u8]);
+        impl Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(io::Error::other("disk gone"));
+                }
+                let n = self.0.read(buf)?;
+                Ok(n)
+            }
+        }
+
+        let filter = filter_with(&["赌博"]);
+        let results: Vec<io::Result<StreamMatch>> = filter.scan_reader(Failing("含赌博".as_bytes())).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().start, 3);
+        assert_eq!(results[1].as_ref().unwrap_err().to_string(), "disk gone");
+    }
+
+    #[cfg(feature = "async-io")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_scan_matches_sync_scan() {
+        use futures_core::Stream;
+        use std::sync::Arc;
+
+        async fn collect(mut stream: AsyncStreamScanner) -> Vec<io::Result<StreamMatch>> {
+            let mut items = Vec::new();
+            while let Some(item) = std::future::poll_fn(|cx| core::pin::Pin::new(&mut stream).poll_next(cx)).await {
+                items.push(item);
+            }
+            items
+        }
+
+        /// A byte stream delivering the input in fixed-size pieces, then an optional error.
+        struct Pieces(VecDeque<io::Result<Vec<u8>>>);
+        impl Stream for Pieces {
+            type Item = io::Result<Vec<u8>>;
+            fn poll_next(
+                mut self: core::pin::Pin<&mut Self>,
+                _: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Option<Self::Item>> {
+                core::task::Poll::Ready(self.0.pop_front())
+            }
+        }
+
+        let filter = Arc::new(filter_with(&["赌博", "色情"]));
+        let input = "第一行赌博\n第二行色@情".repeat(50);
+        let expected = scan(&filter, &input, 64);
+        assert_eq!(expected.len(), 100);
+
+        let from_reader = collect(filter.clone().scan_async_read(io::Cursor::new(input.clone().into_bytes()))).await;
+        assert_eq!(from_reader.into_iter().collect::<io::Result<Vec<_>>>().unwrap(), expected);
+
+        // Pieces of 5 bytes cut characters in half.
+        let pieces =
+            || -> VecDeque<io::Result<Vec<u8>>> { input.as_bytes().chunks(5).map(|c| Ok(c.to_vec())).collect() };
+        let from_stream = collect(filter.clone().scan_async_stream(Pieces(pieces()))).await;
+        assert_eq!(from_stream.into_iter().collect::<io::Result<Vec<_>>>().unwrap(), expected);
+
+        // A failing source: matches before the error, then the error.
+        let mut pieces = pieces();
+        pieces.truncate(4);
+        pieces.push_back(Err(io::Error::other("connection reset")));
+        let failed = collect(filter.scan_async_stream(Pieces(pieces))).await;
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].as_ref().unwrap().word, "赌博");
+        assert_eq!(failed[1].as_ref().unwrap_err().to_string(), "connection reset");
+    }
+
+    #[test]
+    fn test_carry_stays_bounded() {
+        let filter = filter_with(&["赌博"]);
+        let mut scanner = filter.scan_reader(io::repeat(b'x').take(1 << 20)).with_chunk_size(1024);
+        assert!(scanner.next().is_none());
+        assert!(scanner.feeder.core.carry.len() < 8);
+        assert_eq!(scanner.feeder.core.released(), 1 << 20);
+    }
+
+    #[test]
+    fn test_scanner_same_matches_for_every_fragmentation() {
+        let filter = filter_with(&["赌", "赌博", "赌博机", "色情", "abc"]);
+        let input = "赌博机\n含有色情 ab\nc abc 赌@博 色情色情\n赌";
+        let expected: Vec<Match> = filter.find_occurrences(input).into_iter().filter(|m| !m.is_variant).collect();
+        let chars: Vec<(usize, char)> = input.char_indices().collect();
+        for size in 1..=chars.len() {
+            let mut scanner = filter.scanner();
+            let mut found = Vec::new();
+            let mut shown = String::new();
+            for piece in chars.chunks(size) {
+                let (from, to) = (piece[0].0, piece.last().map(|&(i, c)| i + c.len_utf8()).unwrap());
+                found.extend(scanner.push(&input[from..to]));
+                // At most the longest word, plus the noise inside it.
+                assert!(scanner.pending_len() <= "赌博机".len() + " @".len(), "fragment size {size}");
+                shown += &scanner.release('*');
+            }
+            found.extend(scanner.finish());
+            shown += &scanner.release('*');
+            assert_eq!(found, expected, "fragment size {size}");
+            assert_eq!(shown, "***\n含有** ab\nc *** *** ****\n*", "fragment size {size}");
+        }
+    }
+
+    #[test]
+    fn test_noise_after_unfinished_word_is_set_aside() {
+        let filter = filter_with(&["赌博", "色情"]);
+        let mut scanner = filter.scanner();
+        let mut shown = String::new();
+        assert!(scanner.push("赌").is_empty());
+        for _ in 0..20_000 {
+            assert!(scanner.push("!").is_empty());
+            shown += &scanner.release('*');
+        }
+        assert_eq!(scanner.pending_len(), "赌".len());
+        let found = scanner.push("博 色");
+        assert_eq!((found[0].word.as_str(), found[0].start, found[0].end), ("赌博", 0, 20_006));
+        assert_eq!(scanner.push("!!"), []);
+        let found = scanner.push("情");
+        assert_eq!((found[0].word.as_str(), found[0].start, found[0].end), ("色情", 20_007, 20_015));
+        assert_eq!(scanner.finish(), []);
+        shown += &scanner.release('*');
+        assert_eq!(shown, format!("{} ****", "*".repeat(20_002)));
+
+        // Positions after long noise runs, read in small chunks.
+        let input = format!("第一行\n色{}情\n  赌@博", "!".repeat(100));
+        let positions: Vec<(u64, u64, u64, u64)> =
+            scan(&filter, &input, 4).iter().map(|m| (m.start, m.end, m.line, m.column)).collect();
+        assert_eq!(positions, [(10, 116, 2, 1), (119, 126, 3, 3)]);
+    }
+
+    #[test]
+    fn test_scanner_reuse_after_finish() {
+        let filter = filter_with(&["赌博"]);
+        let mut scanner = filter.scanner();
+        assert!(scanner.push("a赌").is_empty());
+        assert_eq!(scanner.finish(), []);
+        // No longer word starts with "赌博", so it is final as soon as it is complete.
+        let found = scanner.push("赌博");
+        assert_eq!((found[0].start, found[0].end), (0, 6));
+        assert_eq!(scanner.finish(), []);
+        assert_eq!(scanner.release('*'), "a赌**");
+    }
+}
@@ -8,14 +8,19 @@
 //! - **Shape** (形似字): characters are compared against a shape-confusable map loaded from
 //!   `dict/shape_map.txt`, where each entry is a full equivalence class (e.g. `睹` ↔ `赌`).
 
+use crate::engine::StaticDict;
 use pinyin::Pinyin;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 /// Variation detector
+#[derive(Clone)]
 pub struct VariantDetector {
     pinyin_map: HashMap<String, Vec<String>>, // The mapping of pinyin to original word
     shape_map: HashMap<char, Vec<char>>,      // SHAPED CLOSE CHARACTER MAPPING
     char_to_pinyin: HashMap<char, String>,    // Character to pinyin mapping
+    pinyin: bool,                             // Pinyin channel enabled
+    shape: bool,                              // Shape channel enabled
 }
 
 impl std::fmt::Debug for VariantDetector {
@@ -24,6 +29,8 @@ impl std::fmt::Debug for VariantDetector {
             .field("pinyin_map_size", &self.pinyin_map.len())
             .field("shape_map_size", &self.shape_map.len())
             .field("char_to_pinyin_size", &self.char_to_pinyin.len())
+            .field("pinyin", &self.pinyin)
+            .field("shape", &self.shape)
             .finish()
     }
 }
@@ -54,10 +61,41 @@ impl VariantDetector {
             pinyin_map: HashMap::new(),
             shape_map: Self::build_shape_map(),
             char_to_pinyin: HashMap::new(),
+            pinyin: true,
+            shape: true,
         }
     }
 
+    /// A detector with no words and the same shape map and channel settings.
+    pub(crate) fn emptied(&self) -> Self {
+        Self { pinyin_map: HashMap::new(), char_to_pinyin: HashMap::new(), ..self.clone() }
+    }
+
+    /// Enable or disable the pinyin channel (on by default)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::VariantDetector;
+    ///
+    /// let mut vd = VariantDetector::new();
+    /// vd.add_word("赌博");
+    /// vd.set_pinyin(false);
+    /// assert!(vd.detect("dubo", &["赌博"]).is_empty());
+    /// assert_eq!(vd.detect("睹博", &["赌博"]), vec!["赌博"]); // shape channel
+    /// ```
+    pub fn set_pinyin(&mut self, enabled: bool) {
+        self.pinyin = enabled;
+    }
+
+    /// Enable or disable the shape channel (on by default)
+    pub fn set_shape(&mut self, enabled: bool) {
+        self.shape = enabled;
+    }
+
     /// Construct pinyin index when adding sensitive words
+    ///
+    /// Adding a word that is already indexed changes nothing.
     pub fn add_word(&mut self, word: &str) {
         let chars_result = Pinyin::chars(word).with_tone_style(pinyin::ToneStyle::None);
         let han_chars: Vec<char> = word.chars().filter(|c| !c.is_ascii()).collect();
@@ -75,10 +113,34 @@ impl VariantDetector {
 
         if !pinyins.is_empty() {
             let pinyin_key = pinyins.join("");
-            self.pinyin_map.entry(pinyin_key).or_default().push(word.to_string());
+            let words = self.pinyin_map.entry(pinyin_key).or_default();
+            if !words.iter().any(|indexed| indexed == word) {
+                words.push(word.to_string());
+            }
         }
     }
 
+    /// Take `words` out of the pinyin index, along with the pinyin of characters no
+    /// remaining word uses.
+    pub(crate) fn remove_words(&mut self, words: &[String]) {
+        let removed: HashSet<&str> = words.iter().map(String::as_str).collect();
+        self.pinyin_map.retain(|_, indexed| {
+            indexed.retain(|word| !removed.contains(word.as_str()));
+            !indexed.is_empty()
+        });
+        let mut orphans: HashSet<char> =
+            words.iter().flat_map(|word| word.chars()).filter(|c| self.char_to_pinyin.contains_key(c)).collect();
+        for word in self.pinyin_map.values().flatten() {
+            if orphans.is_empty() {
+                break;
+            }
+            for c in word.chars() {
+                orphans.remove(&c);
+            }
+        }
+        self.char_to_pinyin.retain(|c, _| !orphans.contains(c));
+    }
+
     /// Detect variants in text
     ///
     /// Returns the subset of `original_words` whose pinyin or shape variant appears in `text`.
@@ -98,16 +160,75 @@ impl VariantDetector {
         let mut variants = Vec::new();
 
         // 1. Detect pinyin variants
-        variants.extend(self.detect_pinyin_variants(text, original_words));
+        if self.pinyin {
+            variants.extend(self.detect_pinyin_variants(text, original_words));
+        }
 
         // 2. Detect shape-near-word variant
-        variants.extend(self.detect_shape_variants(text, original_words));
+        if self.shape {
+            variants.extend(self.detect_shape_variants(text, original_words));
+        }
 
         variants.sort_unstable();
         variants.dedup();
         variants
     }
 
+    /// Detect variants in text, with the byte span each one was found at
+    ///
+    /// Same channels as [`VariantDetector::detect`], but every occurrence is reported with
+    /// its byte range in `text`, ordered by position. A pinyin hit covers the characters
+    /// whose pinyin it spans; a shape hit covers the whole text, since the shape channel
+    /// compares the text as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sensitive_rs::VariantDetector;
+    ///
+    /// let mut vd = VariantDetector::new();
+    /// vd.add_word("赌博");
+    /// assert_eq!(vd.detect_with_spans("含有 dubo", &["赌博"]), vec![(7..11, "赌博")]);
+    /// ```
+    pub fn detect_with_spans<'a>(&'a self, text: &str, original_words: &[&'a str]) -> Vec<(Range<usize>, &'a str)> {
+        let mut spans = Vec::new();
+
+        // 1. Pinyin
+        if self.pinyin {
+            spans.extend(self.pinyin_spans(text, original_words));
+        }
+
+        // 2. Shape: whole-text comparison, as in `detect`.
+        if self.shape {
+            spans
+                .extend(self.detect_shape_variants(text, original_words).into_iter().map(|word| (0..text.len(), word)));
+        }
+
+        spans.sort_unstable_by(|a, b| (a.0.start, a.0.end, a.1).cmp(&(b.0.start, b.0.end, b.1)));
+        spans.dedup();
+        spans
+    }
+
+    /// Pinyin hits with their spans: locate each word's pinyin in the text's pinyin, then
+    /// widen to the characters whose pinyin the hit touches.
+    fn pinyin_spans<'a>(&'a self, text: &str, original_words: &[&'a str]) -> Vec<(Range<usize>, &'a str)> {
+        let (text_pinyin, segments) = self.text_to_pinyin_segments(text);
+        let mut spans = Vec::new();
+        for &word in original_words {
+            let word_pinyin = self.word_to_pinyin(word);
+            if word_pinyin.is_empty() {
+                continue;
+            }
+            for (pinyin_start, hit) in text_pinyin.match_indices(&word_pinyin) {
+                let first = segments.partition_point(|&(p, _)| p <= pinyin_start) - 1;
+                let last = segments.partition_point(|&(p, _)| p < pinyin_start + hit.len()) - 1;
+                let end = segments.get(last + 1).map_or(text.len(), |&(_, t)| t);
+                spans.push((segments[first].1..end, word));
+            }
+        }
+        spans
+    }
+
     /// Detect pinyin variants
     fn detect_pinyin_variants<'a>(&'a self, text: &str, original_words: &[&'a str]) -> Vec<&'a str> {
         let text_pinyin = self.text_to_pinyin(text);
@@ -115,27 +236,55 @@ impl VariantDetector {
         original_words
             .iter()
             .filter(|&&word| {
-                // Construct the pinyin of the word
-                let word_pinyin: String = word
-                    .chars()
-                    .map(|c| {
-                        self.char_to_pinyin.get(&c).cloned().unwrap_or_else(|| c.to_string())
-                        // Safe processing: Return original characters
-                    })
-                    .collect();
-
+                let word_pinyin = self.word_to_pinyin(word);
                 text_pinyin.contains(&word_pinyin)
             })
             .copied()
             .collect()
     }
 
+    /// Construct the pinyin of a dictionary word
+    fn word_to_pinyin(&self, word: &str) -> String {
+        word.chars()
+            .map(|c| {
+                self.char_to_pinyin.get(&c).cloned().unwrap_or_else(|| c.to_string())
+                // Safe processing: Return original characters
+            })
+            .collect()
+    }
+
+    /// Convert text to pinyin, also returning `(pinyin_offset, text_offset)` for the
+    /// start of every character so pinyin hits can be mapped back to the text.
+    fn text_to_pinyin_segments(&self, text: &str) -> (String, Vec<(usize, usize)>) {
+        let extra = self.uncached_pinyin(text);
+        let mut pinyin = String::with_capacity(text.len() * 2);
+        let mut segments = Vec::with_capacity(text.len());
+        for (offset, c) in text.char_indices() {
+            segments.push((pinyin.len(), offset));
+            match self.char_to_pinyin.get(&c).or_else(|| extra.get(&c)) {
+                Some(p) => pinyin.push_str(p),
+                None => pinyin.push(c),
+            }
+        }
+        (pinyin, segments)
+    }
+
     /// Convert text to pinyin
     fn text_to_pinyin(&self, text: &str) -> String {
-        // Build pinyin for uncached characters in batch
+        let extra = self.uncached_pinyin(text);
+
+        text.chars()
+            .map(|c| {
+                self.char_to_pinyin.get(&c).cloned().or_else(|| extra.get(&c).cloned()).unwrap_or_else(|| c.to_string())
+            })
+            .collect()
+    }
+
+    /// Build pinyin for the text's uncached characters in batch
+    fn uncached_pinyin(&self, text: &str) -> HashMap<char, String> {
         let uncached: Vec<char> =
             text.chars().filter(|c| !c.is_ascii() && !self.char_to_pinyin.contains_key(c)).collect();
-        let extra: HashMap<char, String> = if uncached.is_empty() {
+        if uncached.is_empty() {
             HashMap::new()
         } else {
             let uncached_str: String = uncached.iter().collect();
@@ -143,13 +292,7 @@ impl VariantDetector {
                 .into_iter()
                 .zip(Pinyin::chars(&uncached_str).with_tone_style(pinyin::ToneStyle::None).iter())
                 .collect()
-        };
-
-        text.chars()
-            .map(|c| {
-                self.char_to_pinyin.get(&c).cloned().or_else(|| extra.get(&c).cloned()).unwrap_or_else(|| c.to_string())
-            })
-            .collect()
+        }
     }
 
     /// Detect shape-near-word variant
@@ -212,6 +355,65 @@ impl VariantDetector {
     }
 }
 
+impl VariantDetector {
+    /// Append the pinyin index and channel switches to a snapshot payload. The shape map is
+    /// embedded in the crate and not stored.
+    pub(crate) fn encode(&self, e: &mut crate::snapshot::Encoder) {
+        e.bool(self.pinyin);
+        e.bool(self.shape);
+        let mut pinyin_map: Vec<_> = self.pinyin_map.iter().collect();
+        pinyin_map.sort_unstable();
+        e.usize(pinyin_map.len());
+        for (pinyin, words) in pinyin_map {
+            e.str(pinyin);
+            e.strs(words);
+        }
+        let mut char_to_pinyin: Vec<_> = self.char_to_pinyin.iter().collect();
+        char_to_pinyin.sort_unstable();
+        e.usize(char_to_pinyin.len());
+        for (&c, pinyin) in char_to_pinyin {
+            e.char(c);
+            e.str(pinyin);
+        }
+    }
+
+    /// Add the pinyin index of a dictionary compiled at build time.
+    pub(crate) fn add_static(&mut self, dict: &StaticDict) {
+        for (key, word) in dict.pinyin_entries() {
+            self.pinyin_map.entry(key.to_string()).or_default().push(word.to_string());
+        }
+        for &(c, pinyin) in dict.char_pinyin() {
+            self.char_to_pinyin.insert(c, pinyin.to_string());
+        }
+    }
+
+    /// Pinyin keys and the words indexed under them.
+    #[cfg(feature = "codegen")]
+    pub(crate) fn pinyin_entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pinyin_map.iter().flat_map(|(key, words)| words.iter().map(move |word| (key.as_str(), word.as_str())))
+    }
+
+    /// Pinyin of every indexed Han character.
+    #[cfg(feature = "codegen")]
+    pub(crate) fn char_pinyin(&self) -> impl Iterator<Item = (char, &str)> {
+        self.char_to_pinyin.iter().map(|(&c, pinyin)| (c, pinyin.as_str()))
+    }
+
+    /// Read a detector written by [`VariantDetector::encode`].
+    pub(crate) fn decode(d: &mut crate::snapshot::Decoder<'_>) -> Result<Self, crate::snapshot::SnapshotError> {
+        let mut detector = Self::new();
+        detector.pinyin = d.bool()?;
+        detector.shape = d.bool()?;
+        for _ in 0..d.len()? {
+            detector.pinyin_map.insert(d.string()?, d.strings()?);
+        }
+        for _ in 0..d.len()? {
+            detector.char_to_pinyin.insert(d.char()?, d.string()?);
+        }
+        Ok(detector)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +524,31 @@ mod tests {
         assert_eq!(results, vec!["赌博"]);
     }
 
+    #[test]
+    fn test_detect_with_spans_maps_pinyin_to_chars() {
+        let mut vd = VariantDetector::new();
+        vd.add_word("赌博");
+        // 睹 shares 赌's pinyin, so the hit covers the two CJK chars (bytes 6..12).
+        let text = "含有睹博内容";
+        assert_eq!(vd.detect_with_spans(text, &["赌博"]), vec![(6..12, "赌博")]);
+        // Every occurrence is reported.
+        assert_eq!(vd.detect_with_spans("dubo dubo", &["赌博"]), vec![(0..4, "赌博"), (5..9, "赌博")]);
+    }
+
+    #[test]
+    fn test_detect_with_spans_agrees_with_detect() {
+        let mut vd = VariantDetector::new();
+        vd.add_word("赌博");
+        vd.add_word("色情");
+        let words = ["赌博", "色情"];
+        for text in ["dubo and seqing", "睹博", "hello", ""] {
+            let mut from_spans: Vec<&str> = vd.detect_with_spans(text, &words).into_iter().map(|(_, w)| w).collect();
+            from_spans.sort_unstable();
+            from_spans.dedup();
+            assert_eq!(from_spans, vd.detect(text, &words), "text: {text}");
+        }
+    }
+
     #[test]
     fn test_detect_returns_borrowed_slices() {
         // detect returns references into the caller's `original_words` slice,
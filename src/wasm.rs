@@ -4,7 +4,7 @@
 //! [`WasmFilter`] to JavaScript via `wasm-bindgen`. File/network I/O is not
 //! available on WASM — use [`WasmFilter::load_words`] with in-memory text instead.
 
-use crate::Filter;
+use crate::{Filter, Markup};
 use wasm_bindgen::prelude::*;
 
 /// JavaScript-facing wrapper around [`Filter`].
@@ -55,6 +55,13 @@ impl WasmFilter {
         arr
     }
 
+    /// Original text with matches wrapped in `<mark data-word data-kind>`; the rest is
+    /// HTML-escaped, so the result can be assigned to `innerHTML`.
+    #[wasm_bindgen(js_name = annotateHtml)]
+    pub fn annotate_html(&self, text: &str) -> String {
+        self.inner.annotate(text, &Markup::Html)
+    }
+
     /// Replace each matched character with `replacement`.
     pub fn replace(&self, text: &str, replacement: char) -> String {
         self.inner.replace(text, replacement)
@@ -76,6 +83,18 @@ impl WasmFilter {
             }
         }
     }
+
+    /// Add a dictionary compiled into the crate by name (`filter.loadBuiltin('default')`;
+    /// `'default'` needs the `builtin-dict` feature, `'all'` needs `builtin-dict-all`).
+    /// Throws if the dictionary is not compiled in.
+    #[cfg(any(feature = "builtin-dict", feature = "builtin-dict-all"))]
+    #[wasm_bindgen(js_name = loadBuiltin)]
+    pub fn load_builtin(&mut self, name: &str) -> Result<(), JsValue> {
+        let builtin = crate::Builtin::from_name(name)
+            .ok_or_else(|| JsValue::from_str(&format!("built-in dictionary '{name}' is not compiled in")))?;
+        self.inner.add_builtin(builtin);
+        Ok(())
+    }
 }
 
 impl Default for WasmFilter {
@@ -41,6 +41,25 @@ fn test_cli_check_found() {
     assert!(out.contains("Found"), "stdout: {out}");
 }
 
+#[test]
+fn test_cli_check_highlights_inline() {
+    let output = sensitive().args(DICT_ARGS).args(["--color=false", "check", "含有赌博和色情"]).output().unwrap();
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let out = stdout(&output);
+    // Without colour, matches are bracketed inside the original text.
+    assert!(out.contains("含有[赌博]和[色情]"), "stdout: {out}");
+}
+
+#[test]
+fn test_cli_check_strips_terminal_controls() {
+    let input = "\x1b[2J\x1b]0;title\x07含有赌博\u{9b}31m";
+    let output = sensitive().args(DICT_ARGS).args(["--color=true", "check", input]).output().unwrap();
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let out = stdout(&output);
+    assert!(out.contains("\x1b[31m赌博\x1b[0m"), "stdout: {out:?}");
+    assert!(!out.contains("\x1b[2J") && !out.contains('\x07') && !out.contains('\u{9b}'), "stdout: {out:?}");
+}
+
 #[test]
 fn test_cli_validate_clean_exits_zero() {
     let output = sensitive().args(DICT_ARGS).args(["validate", "正常内容"]).output().unwrap();
@@ -81,3 +100,53 @@ fn test_cli_json_output() {
     assert!(out.contains(r#""word""#), "expected a `word` field, got: {out}");
     assert!(out.contains("赌博"), "expected the match in output, got: {out}");
 }
+
+#[test]
+fn test_cli_dict_diff_json_shows_changed_entries() {
+    let dir = std::env::temp_dir().join(format!("sensitive-rs-cli-diff-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("old.txt"), "赌博 | severity=2\n色情\n").unwrap();
+    std::fs::write(dir.join("new.txt"), "赌博 | severity=3 | category=gambling\n诈骗\n").unwrap();
+    let output = sensitive()
+        .args(["--json", "dict", "diff"])
+        .arg(dir.join("old.txt"))
+        .arg(dir.join("new.txt"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let diff: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(diff["added"], serde_json::json!(["诈骗"]));
+    assert_eq!(diff["removed"], serde_json::json!(["色情"]));
+    assert_eq!(
+        diff["changed"],
+        serde_json::json!([{
+            "word": "赌博",
+            "old": { "word": "赌博", "severity": 2 },
+            "new": { "word": "赌博", "category": "gambling", "severity": 3 },
+        }])
+    );
+}
+
+#[test]
+fn test_cli_dict_lint_directory() {
+    let dir = std::env::temp_dir().join(format!("sensitive-rs-cli-lint-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("fraud.txt"), "代开发票\n").unwrap();
+    std::fs::write(dir.join("gambling.txt"), "赌博\n赌.博\n").unwrap();
+    std::fs::write(dir.join("entries.csv"), "word\n赌\n").unwrap();
+    let output = sensitive().args(["--json", "dict", "lint"]).arg(&dir).output().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["entries"], 3);
+    // Only the `.txt` lists are read; the bad entry is line 2 of its own file.
+    let issues = report["issues"].as_array().unwrap();
+    assert!(!issues.is_empty());
+    for issue in issues {
+        assert!(issue["file"].as_str().unwrap().ends_with("gambling.txt"), "{issue}");
+        assert_eq!(issue["line"], 2);
+    }
+}